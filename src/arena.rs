@@ -0,0 +1,52 @@
+use bevy::{prelude::*, render::camera::ScalingMode};
+use rand::Rng;
+
+/// The playfield, centered on the origin. Its size is fixed, and does not follow the window.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Arena {
+    pub size: Vec2,
+}
+
+impl Arena {
+    pub fn new(size: Vec2) -> Self {
+        Self { size }
+    }
+
+    pub fn min(&self) -> Vec2 {
+        -self.size / 2.
+    }
+
+    pub fn max(&self) -> Vec2 {
+        self.size / 2.
+    }
+
+    pub fn random_position(&self, rng: &mut impl Rng) -> Vec2 {
+        let (min, max) = (self.min(), self.max());
+        Vec2 {
+            x: rng.gen_range(min.x..max.x),
+            y: rng.gen_range(min.y..max.y),
+        }
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new(Vec2 { x: 1280., y: 720. })
+    }
+}
+
+/// Scales the camera so the whole arena is visible, whatever the window size. The space left
+/// over on the sides is left empty.
+pub fn fit_camera_to_arena(
+    arena: Res<Arena>,
+    mut q_camera: Query<(&mut OrthographicProjection, Ref<Camera2d>)>,
+) {
+    for (mut projection, camera) in q_camera.iter_mut() {
+        if arena.is_changed() || camera.is_added() {
+            projection.scaling_mode = ScalingMode::AutoMin {
+                min_width: arena.size.x,
+                min_height: arena.size.y,
+            };
+        }
+    }
+}
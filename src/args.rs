@@ -0,0 +1,6 @@
+/// Returns the value following `flag` on the command line, like `--seed 42`.
+pub fn value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    args.find(|arg| arg == flag)?;
+    args.next()
+}
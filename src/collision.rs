@@ -0,0 +1,212 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::PhysicsSet;
+
+/// Side of a [`CollisionGrid`] cell.
+pub const GRID_CELL_SIZE: f32 = 100.;
+
+/// Keeps a [`CollisionGrid`] of everything with a [`Collider`].
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CollisionGrid>().add_systems(
+            FixedUpdate,
+            update_collision_grid.in_set(PhysicsSet::Broadphase),
+        );
+    }
+}
+
+/// The outline of a [`Collider`], centered on the origin.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    /// An axis aligned box of this size.
+    Aabb(Vec2),
+    /// A circle of this radius.
+    Circle(f32),
+}
+
+impl Shape {
+    /// The smallest box around the shape.
+    pub fn size(self) -> Vec2 {
+        match self {
+            Shape::Aabb(size) => size,
+            Shape::Circle(radius) => Vec2::splat(radius * 2.),
+        }
+    }
+}
+
+/// What part of an entity touches other entities, relative to its position.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Collider {
+    pub shape: Shape,
+    pub offset: Vec2,
+}
+
+impl Collider {
+    pub fn aabb(size: Vec2) -> Self {
+        Shape::Aabb(size).into()
+    }
+
+    pub fn circle(radius: f32) -> Self {
+        Shape::Circle(radius).into()
+    }
+
+    pub fn with_offset(self, offset: Vec2) -> Self {
+        Self { offset, ..self }
+    }
+
+    /// Whether this collider, on an entity at `position`, touches `other` on an entity at
+    /// `other_position`.
+    pub fn overlaps(&self, position: Vec2, other: &Collider, other_position: Vec2) -> bool {
+        let (a, b) = (position + self.offset, other_position + other.offset);
+        match (self.shape, other.shape) {
+            (Shape::Aabb(a_size), Shape::Aabb(b_size)) => {
+                let reach = (a_size + b_size) / 2.;
+                let d = (a - b).abs();
+                d.x < reach.x && d.y < reach.y
+            }
+            (Shape::Circle(a_radius), Shape::Circle(b_radius)) => {
+                a.distance_squared(b) < (a_radius + b_radius).powi(2)
+            }
+            (Shape::Aabb(size), Shape::Circle(radius)) => circle_touches_box(b, radius, a, size),
+            (Shape::Circle(radius), Shape::Aabb(size)) => circle_touches_box(a, radius, b, size),
+        }
+    }
+
+    /// How far an entity at `position` has to move to get this collider out of `rect`, the
+    /// shortest way. `None` when they don't touch.
+    pub fn push_out(&self, position: Vec2, rect: Rect) -> Option<Vec2> {
+        let center = position + self.offset;
+        let half = match self.shape {
+            Shape::Aabb(size) => size / 2.,
+            Shape::Circle(radius) => {
+                let closest = center.clamp(rect.min, rect.max);
+                let away = center - closest;
+                if away != Vec2::ZERO {
+                    let distance = away.length();
+                    return (distance < radius).then(|| away / distance * (radius - distance));
+                }
+                // the center is inside, so push it out like a box
+                Vec2::splat(radius)
+            }
+        };
+        [
+            Vec2::new(rect.min.x - (center.x + half.x), 0.),
+            Vec2::new(rect.max.x - (center.x - half.x), 0.),
+            Vec2::new(0., rect.min.y - (center.y + half.y)),
+            Vec2::new(0., rect.max.y - (center.y - half.y)),
+        ]
+        .into_iter()
+        .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .filter(|_| {
+            let d = (center - rect.center()).abs();
+            let reach = half + rect.half_size();
+            d.x < reach.x && d.y < reach.y
+        })
+    }
+
+    /// The first of `others` that this collider, on an entity at `position`, touches.
+    pub fn first_overlap<'a, T>(
+        &self,
+        position: Vec2,
+        others: impl IntoIterator<Item = (T, &'a Collider, Vec2)>,
+    ) -> Option<T> {
+        others
+            .into_iter()
+            .find(|(_, other, other_position)| self.overlaps(position, other, *other_position))
+            .map(|(item, ..)| item)
+    }
+}
+
+impl From<Shape> for Collider {
+    fn from(shape: Shape) -> Self {
+        Self {
+            shape,
+            offset: Vec2::ZERO,
+        }
+    }
+}
+
+fn circle_touches_box(center: Vec2, radius: f32, box_center: Vec2, size: Vec2) -> bool {
+    let closest = center.clamp(box_center - size / 2., box_center + size / 2.);
+    center.distance_squared(closest) < radius * radius
+}
+
+/// Every [`Collider`], sorted into square cells by where it is, so overlap queries only look at
+/// what is nearby. Rebuilt every physics step, after everything has moved, so it should only be
+/// read in `PhysicsSet::Contacts`.
+///
+/// Children, like a carried USB, are left out. They go wherever their parent goes.
+#[derive(Resource, Default)]
+pub struct CollisionGrid {
+    entries: Vec<(Entity, Collider, Vec2)>,
+    indices: HashMap<Entity, usize>,
+    cells: HashMap<IVec2, Vec<usize>>,
+}
+
+impl CollisionGrid {
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.indices.clear();
+        self.cells.clear();
+    }
+
+    /// Adds `entity`, with `collider` at `position`.
+    pub fn insert(&mut self, entity: Entity, collider: Collider, position: Vec2) {
+        let index = self.entries.len();
+        for cell in Self::cells(collider, position) {
+            self.cells.entry(cell).or_default().push(index);
+        }
+        self.entries.push((entity, collider, position));
+        self.indices.insert(entity, index);
+    }
+
+    /// The collider of `entity` and where it was, if it is in the grid.
+    pub fn get(&self, entity: Entity) -> Option<(Collider, Vec2)> {
+        self.indices.get(&entity).map(|index| {
+            let (_, collider, position) = self.entries[*index];
+            (collider, position)
+        })
+    }
+
+    /// Every entity that touches `collider` at `position`, in the order they were inserted.
+    pub fn overlapping(&self, collider: &Collider, position: Vec2) -> Vec<Entity> {
+        let mut candidates: Vec<usize> = Self::cells(*collider, position)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+            .into_iter()
+            .map(|index| self.entries[index])
+            .filter(|(_, other, other_position)| {
+                collider.overlaps(position, other, *other_position)
+            })
+            .map(|(entity, ..)| entity)
+            .collect()
+    }
+
+    /// The cells that the box around `collider` at `position` is in.
+    fn cells(collider: Collider, position: Vec2) -> impl Iterator<Item = IVec2> {
+        let center = position + collider.offset;
+        let half = collider.shape.size() / 2.;
+        let min = ((center - half) / GRID_CELL_SIZE).floor().as_ivec2();
+        let max = ((center + half) / GRID_CELL_SIZE).floor().as_ivec2();
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+    }
+}
+
+/// Puts every collider without a parent in the grid.
+fn update_collision_grid(
+    mut grid: ResMut<CollisionGrid>,
+    q_colliders: Query<(Entity, &Transform, &Collider), Without<Parent>>,
+) {
+    grid.clear();
+    for (entity, trans, collider) in q_colliders.iter() {
+        grid.insert(entity, *collider, trans.translation.truncate());
+    }
+}
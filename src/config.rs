@@ -0,0 +1,214 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collision::Shape,
+    difficulty::{DifficultyCurve, DifficultyPresets},
+    enemies::{CopKind, CopTypes},
+    replay::{Recorder, Replay},
+    State,
+};
+
+pub const CONFIG_PATH: &str = "config.ron";
+
+/// Loads [`GameConfig`] from `assets/config.ron`, and reloads it when the file changes.
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameConfig>()
+            .add_asset::<GameConfig>()
+            .init_asset_loader::<GameConfigLoader>()
+            .add_systems(Startup, load_config)
+            .add_systems(
+                Update,
+                update_config.run_if(not(resource_exists::<Replay>())),
+            );
+    }
+}
+
+/// Every gameplay constant, loaded from `assets/config.ron`.
+///
+/// The resource starts out with the default values, and is overwritten whenever the asset is
+/// loaded or changed on disk, but not in the middle of a recorded run. Replays keep the config
+/// they were recorded with instead.
+#[derive(Resource, Serialize, Deserialize, TypeUuid, TypePath, Clone, Debug)]
+#[uuid = "4b1a6a0e-2f0b-4d8e-9a51-6d3c2b7e9f10"]
+#[serde(default)]
+pub struct GameConfig {
+    /// What of the thief touches cops, items and walls.
+    pub player_hitbox: Shape,
+    /// What of a PC a USB has to touch to go in.
+    pub pc_hitbox: Shape,
+    /// What of a USB the thief has to touch to pick it up.
+    pub usb_hitbox: Shape,
+    pub enemy_speed_increase: f32,
+    pub extra_pc_chance: f32,
+    pub popup_chance_per_sec: f32,
+    pub player_speed: f32,
+    /// Speed of a dash, relative to `player_speed`.
+    pub dash_multiplier: f32,
+    /// Seconds a dash lasts.
+    pub dash_time: f32,
+    /// Seconds from the start of one dash until the next one can start.
+    pub dash_cooldown: f32,
+    /// Whether cops can't catch the thief while dashing.
+    pub dash_invulnerable: bool,
+    /// Seconds the after-images of a dash take to fade.
+    pub after_image_time: f32,
+    pub pull_velocity: f32,
+    /// Fraction of the velocity that is kept every physics step.
+    pub drag: f32,
+    pub goal_margin: f32,
+    pub starting_enemies: u32,
+    /// Seconds ahead of the thief that pursuing cops aim for.
+    pub pursuit_lead: f32,
+    /// New PCs, USBs and cops appear at least this far from the thief, when there is room.
+    pub spawn_player_distance: f32,
+    /// New PCs and USBs appear at least this far from the cops, when there is room.
+    pub spawn_cop_distance: f32,
+    /// New PCs and USBs appear at least this far from other items, when there is room.
+    pub spawn_item_distance: f32,
+    /// Space kept free along the edges of the arena when placing items.
+    pub spawn_margin: f32,
+    /// Seconds between the warning of a spawn and the spawn. Things that are there when the run
+    /// starts appear right away.
+    pub spawn_warning_time: f32,
+    pub difficulty_presets: DifficultyPresets,
+    pub difficulty_curve: DifficultyCurve,
+    pub cop_types: CopTypes,
+    /// The kinds of cops the difficulty sends in, in order. Starts over at the end.
+    pub cop_order: Vec<CopKind>,
+    /// Times the thief can be caught before the run ends.
+    pub lives: u32,
+    /// Ends the run the first time the thief is caught, whatever `lives` says.
+    pub one_hit: bool,
+    /// Seconds the thief can't be caught after losing a life.
+    pub capture_grace: f32,
+    pub seed: Option<u64>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            player_hitbox: Shape::Circle(25.),
+            pc_hitbox: Shape::Aabb(Vec2::new(70., 60.)),
+            usb_hitbox: Shape::Circle(20.),
+            enemy_speed_increase: 6.0,
+            extra_pc_chance: 0.25,
+            popup_chance_per_sec: 0.1,
+            player_speed: 240.,
+            dash_multiplier: 4.,
+            dash_time: 0.15,
+            dash_cooldown: 0.8,
+            dash_invulnerable: true,
+            after_image_time: 0.25,
+            pull_velocity: 360.,
+            drag: 0.5,
+            goal_margin: 6.,
+            starting_enemies: 2,
+            pursuit_lead: 0.4,
+            spawn_player_distance: 250.,
+            spawn_cop_distance: 200.,
+            spawn_item_distance: 80.,
+            spawn_margin: 40.,
+            spawn_warning_time: 1.,
+            difficulty_presets: default(),
+            difficulty_curve: default(),
+            cop_types: default(),
+            cop_order: vec![
+                CopKind::Officer,
+                CopKind::Officer,
+                CopKind::Sprinter,
+                CopKind::Detective,
+                CopKind::Swat,
+                CopKind::Officer,
+                CopKind::Sprinter,
+                CopKind::Swat,
+            ],
+            lives: 3,
+            one_hit: false,
+            capture_grace: 2.,
+            seed: None,
+        }
+    }
+}
+
+impl GameConfig {
+    /// The box around the biggest collider of the cops, the thief, PCs and USBs. Paths and spawns
+    /// keep this much room around them, so whatever walks or appears there fits.
+    pub fn clearance(&self) -> Vec2 {
+        CopKind::ALL
+            .iter()
+            .map(|kind| self.cop_types.get(*kind).hitbox)
+            .chain([self.player_hitbox, self.pc_hitbox, self.usb_hitbox])
+            .map(Shape::size)
+            .fold(Vec2::ZERO, Vec2::max)
+    }
+}
+
+#[derive(Resource)]
+pub struct GameConfigHandle(pub Handle<GameConfig>);
+
+#[derive(Default)]
+pub struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let config = ron::de::from_bytes::<GameConfig>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+fn load_config(mut cmd: Commands, asset_server: Res<AssetServer>) {
+    cmd.insert_resource(GameConfigHandle(asset_server.load(CONFIG_PATH)));
+}
+
+/// Copies the loaded asset into the resource. While a run is being recorded the change waits
+/// until the run ends, so the recording replays with the config it stored.
+fn update_config(
+    mut reader: EventReader<AssetEvent<GameConfig>>,
+    assets: Res<Assets<GameConfig>>,
+    handle: Res<GameConfigHandle>,
+    mut config: ResMut<GameConfig>,
+    recorder: Option<Res<Recorder>>,
+    state: Res<bevy::ecs::schedule::State<State>>,
+    mut pending: Local<bool>,
+) {
+    for event in reader.iter() {
+        match event {
+            AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }
+                if *changed == handle.0 =>
+            {
+                *pending = true;
+            }
+            _ => {}
+        }
+    }
+
+    let recording = recorder.is_some() && matches!(state.get(), State::InGame | State::Paused);
+    if !*pending || recording {
+        return;
+    }
+    if let Some(loaded) = assets.get(&handle.0) {
+        info!("loaded {CONFIG_PATH}");
+        *config = loaded.clone();
+        *pending = false;
+    }
+}
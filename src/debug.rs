@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::{Collider, Shape},
+    config::GameConfig,
+    enemies::{CopState, Enemy},
+    pathfinding::Path,
+};
+
+/// Debug drawing, toggled with F3.
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugView>().add_systems(
+            Update,
+            (toggle_debug_view, add_cop_labels, draw_cops, draw_colliders).chain(),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DebugView(pub bool);
+
+/// Shows the kind and the state of the cop it is a child of.
+#[derive(Component)]
+pub struct CopLabel;
+
+fn toggle_debug_view(keyboard_input: Res<Input<KeyCode>>, mut debug_view: ResMut<DebugView>) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        debug_view.0 = !debug_view.0;
+    }
+}
+
+fn cop_color(state: &CopState) -> Color {
+    match state {
+        CopState::Patrol => Color::GREEN,
+        CopState::Notice(_) => Color::YELLOW,
+        CopState::Pursue => Color::RED,
+        CopState::Search(_) => Color::ORANGE,
+    }
+}
+
+fn add_cop_labels(mut cmd: Commands, q_enemies: Query<Entity, Added<Enemy>>) {
+    for entity in q_enemies.iter() {
+        cmd.entity(entity).with_children(|parent| {
+            parent.spawn((
+                CopLabel,
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 24.,
+                            ..default()
+                        },
+                    ),
+                    transform: Transform::from_xyz(0., 50., 100.),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+            ));
+        });
+    }
+}
+
+/// Draws each cop's field of view, its path to its goal and where it last saw the thief, colored
+/// by its state.
+fn draw_cops(
+    debug_view: Res<DebugView>,
+    config: Res<GameConfig>,
+    q_enemies: Query<(&GlobalTransform, &Enemy, &Path)>,
+    mut q_labels: Query<(&Parent, &mut Text, &mut Visibility), With<CopLabel>>,
+    mut gizmos: Gizmos,
+) {
+    for (parent, mut text, mut visibility) in q_labels.iter_mut() {
+        *visibility = if debug_view.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if let Ok((_, enemy, _)) = q_enemies.get(parent.get()) {
+            text.sections[0].value = format!("{} {}", enemy.kind.name(), enemy.state.name());
+            text.sections[0].style.color = cop_color(&enemy.state);
+        }
+    }
+
+    if !debug_view.0 {
+        return;
+    }
+
+    for (trans, enemy, path) in q_enemies.iter() {
+        let pos = trans.translation().truncate();
+        let cop = config.cop_types.get(enemy.kind);
+        let color = cop_color(&enemy.state);
+        let cone = cop.vision_cone.to_radians();
+        let facing = enemy.facing.x.atan2(enemy.facing.y);
+
+        gizmos.circle_2d(pos, cop.notice_radius, color);
+        gizmos
+            .arc_2d(pos, facing, cone, cop.vision_radius, color)
+            .segments(32);
+        for side in [-cone / 2., cone / 2.] {
+            let edge = Vec2::from_angle(-side).rotate(enemy.facing);
+            gizmos.line_2d(pos, pos + edge * cop.vision_radius, color);
+        }
+        gizmos.linestrip_2d(
+            std::iter::once(pos).chain(path.waypoints()),
+            color.with_a(0.4),
+        );
+        if !matches!(enemy.state, CopState::Patrol) {
+            gizmos.circle_2d(enemy.last_seen, 10., color);
+        }
+    }
+}
+
+/// Draws the outline of every [`Collider`].
+fn draw_colliders(
+    debug_view: Res<DebugView>,
+    q_colliders: Query<(&GlobalTransform, &Collider)>,
+    mut gizmos: Gizmos,
+) {
+    if !debug_view.0 {
+        return;
+    }
+
+    for (trans, collider) in q_colliders.iter() {
+        let center = trans.translation().truncate() + collider.offset;
+        match collider.shape {
+            Shape::Aabb(size) => gizmos.rect_2d(center, 0., size, Color::CYAN),
+            Shape::Circle(radius) => {
+                gizmos.circle_2d(center, radius, Color::CYAN);
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::GameConfig,
+    enemies::{AddEnemy, CopKind},
+    Common, GameSet,
+};
+
+/// Scales the run by the chosen [`Difficulty`], and makes it harder as the score and the time
+/// go up.
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .add_systems(Update, update_difficulty.in_set(GameSet::Rules));
+    }
+}
+
+/// The difficulty preset of the next run, picked on the level select screen.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Nightmare,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Nightmare => "nightmare",
+        }
+    }
+
+    /// The next harder preset, going back to the easiest after the hardest.
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|d| *d == self).unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// Multipliers on the gameplay constants that make a run easier or harder.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct DifficultyScale {
+    /// Of `GameConfig::starting_enemies`.
+    pub cops: f32,
+    /// Of `Common::enemy_speed`.
+    pub cop_speed: f32,
+    /// Of `GameConfig::popup_chance_per_sec`.
+    pub popup_chance: f32,
+    /// Of the time a download takes.
+    pub download_time: f32,
+}
+
+impl Default for DifficultyScale {
+    fn default() -> Self {
+        Self {
+            cops: 1.,
+            cop_speed: 1.,
+            popup_chance: 1.,
+            download_time: 1.,
+        }
+    }
+}
+
+impl DifficultyScale {
+    /// `self`, grown by `growth` for every level of the curve.
+    pub fn grown(&self, growth: &DifficultyScale, level: f32) -> Self {
+        let grow = |value: f32, growth: f32| value * (1. + growth * level).max(0.);
+        Self {
+            cops: grow(self.cops, growth.cops),
+            cop_speed: grow(self.cop_speed, growth.cop_speed),
+            popup_chance: grow(self.popup_chance, growth.popup_chance),
+            download_time: grow(self.download_time, growth.download_time),
+        }
+    }
+}
+
+/// The [`DifficultyScale`] of every [`Difficulty`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct DifficultyPresets {
+    pub easy: DifficultyScale,
+    pub normal: DifficultyScale,
+    pub hard: DifficultyScale,
+    pub nightmare: DifficultyScale,
+}
+
+impl DifficultyPresets {
+    pub fn get(&self, difficulty: Difficulty) -> DifficultyScale {
+        match difficulty {
+            Difficulty::Easy => self.easy,
+            Difficulty::Normal => self.normal,
+            Difficulty::Hard => self.hard,
+            Difficulty::Nightmare => self.nightmare,
+        }
+    }
+}
+
+impl Default for DifficultyPresets {
+    fn default() -> Self {
+        let scale = |cops, cop_speed, popup_chance, download_time| DifficultyScale {
+            cops,
+            cop_speed,
+            popup_chance,
+            download_time,
+        };
+        Self {
+            easy: scale(0.5, 0.8, 0.5, 0.75),
+            normal: scale(1., 1., 1., 1.),
+            hard: scale(1.5, 1.2, 1.5, 1.25),
+            nightmare: scale(2., 1.4, 2.5, 1.5),
+        }
+    }
+}
+
+/// How a run gets harder. The level of the curve grows with the score and the time played, up
+/// to `max_level`, and every level grows the preset by `growth`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct DifficultyCurve {
+    pub per_score: f32,
+    pub per_minute: f32,
+    pub max_level: f32,
+    pub growth: DifficultyScale,
+}
+
+impl DifficultyCurve {
+    pub fn level(&self, score: u32, time: std::time::Duration) -> f32 {
+        (score as f32 * self.per_score + time.as_secs_f32() / 60. * self.per_minute)
+            .min(self.max_level)
+    }
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        Self {
+            per_score: 0.25,
+            per_minute: 0.5,
+            max_level: 4.,
+            growth: DifficultyScale {
+                cops: 0.25,
+                cop_speed: 0.1,
+                popup_chance: 0.25,
+                download_time: 0.1,
+            },
+        }
+    }
+}
+
+/// Updates `Common::difficulty`, and sends in cops in the order of `GameConfig::cop_order`
+/// until it has sent as many as it asks for. This is also what sends in the cops at the start of
+/// a run.
+fn update_difficulty(
+    mut common: ResMut<Common>,
+    difficulty: Res<Difficulty>,
+    config: Res<GameConfig>,
+    mut w_enemy: EventWriter<AddEnemy>,
+) {
+    let curve = &config.difficulty_curve;
+    let level = curve.level(common.score, common.time);
+    common.difficulty = config
+        .difficulty_presets
+        .get(*difficulty)
+        .grown(&curve.growth, level);
+
+    let cops = (config.starting_enemies as f32 * common.difficulty.cops).round() as u32;
+    while common.curve_cops < cops {
+        w_enemy.send(AddEnemy(next_cop(&mut common, &config)));
+        common.curve_cops += 1;
+    }
+}
+
+/// The kind of the next cop to send in, going round `GameConfig::cop_order`.
+pub(crate) fn next_cop(common: &mut Common, config: &GameConfig) -> CopKind {
+    let order = &config.cop_order;
+    let kind = order
+        .get(common.cops_sent as usize % order.len().max(1))
+        .copied()
+        .unwrap_or(CopKind::Officer);
+    common.cops_sent += 1;
+    kind
+}
@@ -0,0 +1,518 @@
+use bevy::prelude::*;
+use bevy_svg::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::{
+    arena::Arena,
+    collision::{Collider, Shape},
+    config::GameConfig,
+    despawn_on_end_run,
+    items::{Pc, Usb},
+    level::CurrentLevel,
+    movement::Velocity,
+    obstacles::{line_of_sight, random_free_position, Obstacle},
+    pathfinding::{NavGrid, Path},
+    player::Player,
+    rng::GameRng,
+    spawning::{pick_cop_door, spawn_warning, Spawn, SpawnRules, SpawnWarning, SpawnWarningAssets},
+    AssetPool, Common, GameSet, PhysicsSet,
+};
+
+pub struct EnemiesPlugin;
+
+impl Plugin for EnemiesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AddEnemy>()
+            .add_systems(FixedUpdate, update_enemies.in_set(PhysicsSet::Steering))
+            .add_systems(
+                Update,
+                (spawn_warned_cops, add_enemy)
+                    .chain()
+                    .in_set(GameSet::SpawnEnemies),
+            );
+        despawn_on_end_run::<Enemy>(app);
+    }
+}
+
+/// Sends in a cop of the given kind.
+#[derive(Event)]
+pub struct AddEnemy(pub CopKind);
+
+/// The kinds of cops. What each of them is like is in [`CopType`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CopKind {
+    /// Walks at the usual speed, and patrols anywhere.
+    Officer,
+    /// Runs fast, until it is out of breath.
+    Sprinter,
+    /// Always knows where the thief is while it carries a USB.
+    Detective,
+    /// Big, and stands guard next to the PCs.
+    Swat,
+}
+
+impl CopKind {
+    pub const ALL: [CopKind; 4] = [
+        CopKind::Officer,
+        CopKind::Sprinter,
+        CopKind::Detective,
+        CopKind::Swat,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CopKind::Officer => "officer",
+            CopKind::Sprinter => "sprinter",
+            CopKind::Detective => "detective",
+            CopKind::Swat => "swat",
+        }
+    }
+}
+
+/// Everything that makes one [`CopKind`] different from another, loaded from the config.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CopType {
+    /// Image of the cop, relative to `assets`.
+    pub sprite: String,
+    /// Walking speed, relative to `Common::enemy_speed`.
+    pub speed: f32,
+    /// Speed while pursuing the thief, relative to `Common::enemy_speed`.
+    pub pursuit_speed: f32,
+    /// Seconds the cop can keep up `pursuit_speed`, after which it pursues at `speed` until the
+    /// pursuit ends. Zero for no limit.
+    pub stamina: f32,
+    /// Stamina regained every second when not pursuing.
+    pub stamina_recovery: f32,
+    /// The cop catches the thief when this touches it.
+    pub hitbox: Shape,
+    /// How far the cop sees in front of it.
+    pub vision_radius: f32,
+    /// Width of the field of view, in degrees.
+    pub vision_cone: f32,
+    /// The cop notices the thief this close whatever way it faces.
+    pub notice_radius: f32,
+    /// Seconds the cop stands still after spotting the thief, before giving chase.
+    pub notice_time: f32,
+    /// Seconds the cop searches where the thief was last seen, before giving up.
+    pub search_time: f32,
+    /// Knows where the thief is while it carries a USB, even without seeing it.
+    pub tracks_usb: bool,
+    /// Patrols to the PCs instead of anywhere in the arena.
+    pub guards_pcs: bool,
+}
+
+impl Default for CopType {
+    fn default() -> Self {
+        Self {
+            sprite: "police.svg".to_string(),
+            speed: 1.,
+            pursuit_speed: 1.4,
+            stamina: 0.,
+            stamina_recovery: 0.,
+            hitbox: Shape::Aabb(Vec2::new(50., 50.)),
+            vision_radius: 260.,
+            vision_cone: 100.,
+            notice_radius: 70.,
+            notice_time: 0.4,
+            search_time: 4.,
+            tracks_usb: false,
+            guards_pcs: false,
+        }
+    }
+}
+
+/// The [`CopType`] of every [`CopKind`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CopTypes {
+    pub officer: CopType,
+    pub sprinter: CopType,
+    pub detective: CopType,
+    pub swat: CopType,
+}
+
+impl CopTypes {
+    pub fn get(&self, kind: CopKind) -> &CopType {
+        match kind {
+            CopKind::Officer => &self.officer,
+            CopKind::Sprinter => &self.sprinter,
+            CopKind::Detective => &self.detective,
+            CopKind::Swat => &self.swat,
+        }
+    }
+}
+
+impl Default for CopTypes {
+    fn default() -> Self {
+        Self {
+            officer: CopType::default(),
+            sprinter: CopType {
+                sprite: "sprinter.svg".to_string(),
+                speed: 1.1,
+                pursuit_speed: 2.5,
+                stamina: 2.,
+                stamina_recovery: 0.5,
+                hitbox: Shape::Circle(20.),
+                ..default()
+            },
+            detective: CopType {
+                sprite: "detective.svg".to_string(),
+                speed: 1.,
+                pursuit_speed: 1.5,
+                vision_radius: 320.,
+                search_time: 8.,
+                tracks_usb: true,
+                ..default()
+            },
+            swat: CopType {
+                sprite: "swat.svg".to_string(),
+                speed: 0.7,
+                pursuit_speed: 1.2,
+                hitbox: Shape::Circle(35.),
+                notice_radius: 100.,
+                vision_cone: 140.,
+                guards_pcs: true,
+                ..default()
+            },
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Enemy {
+    pub kind: CopKind,
+    pub state: CopState,
+    pub change_goal: Timer,
+    /// Where the cop is walking to.
+    pub goal: Vec2,
+    /// The way the cop looks, which is the way it last walked.
+    pub facing: Vec2,
+    /// Where the cop last saw the thief.
+    pub last_seen: Vec2,
+    /// Seconds left of pursuing at full speed.
+    pub stamina: f32,
+}
+
+/// What a cop is doing. Patrolling cops that see the thief notice it, then pursue it. When they
+/// lose sight of it they search where it was last seen, and give up and patrol again when the
+/// search time runs out.
+#[derive(Clone, Debug)]
+pub enum CopState {
+    /// Walks to a random point in the arena, or to a PC for cops that guard them, picking a new
+    /// one every `change_goal`.
+    Patrol,
+    /// Has just spotted the thief, and stands still for a moment before giving chase.
+    Notice(Timer),
+    /// Runs to where the thief is heading.
+    Pursue,
+    /// Walks to where the thief was last seen, and looks around.
+    Search(Timer),
+}
+
+impl CopState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CopState::Patrol => "patrol",
+            CopState::Notice(_) => "notice",
+            CopState::Pursue => "pursue",
+            CopState::Search(_) => "search",
+        }
+    }
+}
+
+impl Enemy {
+    pub fn new(kind: CopKind, goal: Vec2, config: &GameConfig, rng: &mut impl Rng) -> Self {
+        Self {
+            kind,
+            stamina: config.cop_types.get(kind).stamina,
+            state: CopState::Patrol,
+            change_goal: Timer::from_seconds(rng.gen_range(1.0..10.0), TimerMode::Repeating),
+            goal,
+            facing: Vec2::X,
+            last_seen: goal,
+        }
+    }
+
+    /// Whether a cop at `position` can see `target`, past the `obstacles`.
+    pub fn sees(
+        &self,
+        position: Vec2,
+        target: Vec2,
+        obstacles: &[Rect],
+        config: &GameConfig,
+    ) -> bool {
+        let cop = config.cop_types.get(self.kind);
+        let to_target = target - position;
+        let distance = to_target.length();
+        let in_view = distance <= cop.notice_radius
+            || (distance <= cop.vision_radius
+                && self.facing.angle_between(to_target).abs() <= cop.vision_cone.to_radians() / 2.);
+        in_view && line_of_sight(position, target, obstacles)
+    }
+
+    /// Sends the cop to search `position`, as if it had just lost sight of the thief there.
+    pub fn alert(&mut self, position: Vec2, config: &GameConfig) {
+        self.last_seen = position;
+        self.search(config);
+    }
+
+    fn search(&mut self, config: &GameConfig) {
+        self.goal = self.last_seen;
+        let search_time = config.cop_types.get(self.kind).search_time;
+        self.state = CopState::Search(Timer::from_seconds(search_time, TimerMode::Once));
+    }
+
+    /// Somewhere new to patrol to: one of the `pcs` for cops that guard them, and anywhere else
+    /// otherwise.
+    fn patrol_goal(
+        &self,
+        pcs: &[Vec2],
+        arena: &Arena,
+        obstacles: &[Rect],
+        config: &GameConfig,
+        rng: &mut impl Rng,
+    ) -> Vec2 {
+        if config.cop_types.get(self.kind).guards_pcs && !pcs.is_empty() {
+            pcs[rng.gen_range(0..pcs.len())]
+        } else {
+            let size = config.cop_types.get(self.kind).hitbox.size();
+            random_free_position(arena, obstacles, size, rng)
+        }
+    }
+
+    /// Moves the state machine one physics step ahead. `thief` is the position and velocity of
+    /// the thief, if the cop can see it.
+    fn think(
+        &mut self,
+        position: Vec2,
+        thief: Option<(Vec2, Vec2)>,
+        dt: Duration,
+        pcs: &[Vec2],
+        arena: &Arena,
+        obstacles: &[Rect],
+        config: &GameConfig,
+        rng: &mut impl Rng,
+    ) {
+        if let Some((thief, _)) = thief {
+            self.last_seen = thief;
+        }
+
+        match (&mut self.state, thief) {
+            (CopState::Patrol, None) => {
+                if self.change_goal.tick(dt).just_finished() {
+                    self.goal = self.patrol_goal(pcs, arena, obstacles, config, rng);
+                }
+            }
+            (CopState::Patrol, Some(_)) => {
+                let notice_time = config.cop_types.get(self.kind).notice_time;
+                self.state = CopState::Notice(Timer::from_seconds(notice_time, TimerMode::Once));
+            }
+            (CopState::Notice(timer), Some((thief, _))) => {
+                self.facing = (thief - position).normalize_or_zero();
+                if timer.tick(dt).finished() {
+                    self.state = CopState::Pursue;
+                }
+            }
+            (CopState::Pursue | CopState::Search(_), Some((thief, velocity))) => {
+                self.state = CopState::Pursue;
+                self.goal = thief + velocity * config.pursuit_lead;
+            }
+            (CopState::Notice(_) | CopState::Pursue, None) => self.search(config),
+            (CopState::Search(timer), None) => {
+                if timer.tick(dt).finished() {
+                    // give up
+                    self.state = CopState::Patrol;
+                    self.goal = self.patrol_goal(pcs, arena, obstacles, config, rng);
+                }
+            }
+        }
+    }
+}
+
+fn update_enemies(
+    mut query: Query<(&Transform, &mut Velocity, &mut Enemy, &mut Path), Without<Player>>,
+    q_player: Query<(&Transform, &Velocity, &Children), With<Player>>,
+    q_usbs: Query<(), With<Usb>>,
+    q_pcs: Query<&Transform, With<Pc>>,
+    q_obstacles: Query<(&Transform, &Obstacle)>,
+    fixed_time: Res<FixedTime>,
+    arena: Res<Arena>,
+    nav_grid: Res<NavGrid>,
+    common: Res<Common>,
+    config: Res<GameConfig>,
+    mut rng: ResMut<GameRng>,
+) {
+    /// How fast searching cops look around, in radians per second.
+    const SEARCH_TURN_SPEED: f32 = 2.;
+
+    let player = q_player.get_single().ok();
+    let thief = player.map(|(trans, vel, _)| (trans.translation.truncate(), vel.0));
+    let carries_usb =
+        player.is_some_and(|(_, _, children)| children.iter().any(|child| q_usbs.contains(*child)));
+    let pcs: Vec<Vec2> = q_pcs.iter().map(|t| t.translation.truncate()).collect();
+    let obstacles: Vec<Rect> = q_obstacles
+        .iter()
+        .map(|(trans, obstacle)| obstacle.rect(trans))
+        .collect();
+    let dt = fixed_time.period;
+
+    for (trans, mut vel, mut enemy, mut path) in query.iter_mut() {
+        let cop = config.cop_types.get(enemy.kind);
+        let pos = trans.translation.truncate();
+        let seen = thief.filter(|(thief, _)| {
+            (cop.tracks_usb && carries_usb) || enemy.sees(pos, *thief, &obstacles, &config)
+        });
+        enemy.think(pos, seen, dt, &pcs, &arena, &obstacles, &config, &mut *rng);
+
+        let pursuing = matches!(enemy.state, CopState::Pursue);
+        let sprinting = pursuing && (cop.stamina <= 0. || 0. < enemy.stamina);
+        if pursuing {
+            enemy.stamina = (enemy.stamina - dt.as_secs_f32()).max(0.);
+        } else {
+            enemy.stamina =
+                (enemy.stamina + cop.stamina_recovery * dt.as_secs_f32()).min(cop.stamina);
+        }
+
+        let speed = common.enemy_speed
+            * common.difficulty.cop_speed
+            * match enemy.state {
+                CopState::Notice(_) => continue,
+                CopState::Pursue if sprinting => cop.pursuit_speed,
+                CopState::Pursue | CopState::Patrol | CopState::Search(_) => cop.speed,
+            };
+        if let Some(waypoint) = path.next_waypoint(pos, enemy.goal, &nav_grid, config.goal_margin) {
+            let dir = (waypoint - pos).normalize_or_zero();
+            vel.0 += dir * speed;
+            enemy.facing = dir;
+        } else if let CopState::Search(_) = enemy.state {
+            let turn = Vec2::from_angle(SEARCH_TURN_SPEED * fixed_time.period.as_secs_f32());
+            enemy.facing = turn.rotate(enemy.facing);
+        }
+    }
+}
+
+/// Sends new cops in through the level's doors, away from the thief. They come in right away
+/// when the run starts, and after a [`SpawnWarning`] later on.
+fn add_enemy(
+    mut cmd: Commands,
+    mut r: EventReader<AddEnemy>,
+    q_obstacles: Query<(&Transform, &Obstacle)>,
+    q_player: Query<&Transform, With<Player>>,
+    arena: Res<Arena>,
+    current_level: Res<CurrentLevel>,
+    common: Res<Common>,
+    config: Res<GameConfig>,
+    asset_pool: Res<AssetPool>,
+    warning_assets: Res<SpawnWarningAssets>,
+    mut rng: ResMut<GameRng>,
+) {
+    let obstacles: Vec<Rect> = q_obstacles
+        .iter()
+        .map(|(trans, obstacle)| obstacle.rect(trans))
+        .collect();
+    let player = q_player.get_single().ok().map(|t| t.translation.truncate());
+    let rules = SpawnRules::new(&config);
+    for AddEnemy(kind) in r.iter() {
+        let doors = current_level.level.cop_doors_or_corner();
+        let door = pick_cop_door(&doors, player, &rules, &mut *rng);
+        if common.is_added() {
+            spawn_cop(
+                &mut cmd,
+                *kind,
+                door,
+                &arena,
+                &obstacles,
+                &config,
+                &asset_pool,
+                &mut rng,
+            );
+        } else {
+            spawn_warning(&mut cmd, Spawn::Cop(*kind), door, &config, &warning_assets);
+        }
+    }
+}
+
+/// Replaces the warnings for cops that have run out with the cops.
+fn spawn_warned_cops(
+    mut cmd: Commands,
+    q_warnings: Query<(Entity, &Transform, &SpawnWarning)>,
+    q_obstacles: Query<(&Transform, &Obstacle)>,
+    arena: Res<Arena>,
+    config: Res<GameConfig>,
+    asset_pool: Res<AssetPool>,
+    mut rng: ResMut<GameRng>,
+) {
+    let obstacles: Vec<Rect> = q_obstacles
+        .iter()
+        .map(|(trans, obstacle)| obstacle.rect(trans))
+        .collect();
+    for (entity, trans, warning) in q_warnings.iter() {
+        let Spawn::Cop(kind) = warning.spawn else {
+            continue;
+        };
+        if warning.timer.finished() {
+            cmd.entity(entity).despawn_recursive();
+            let position = trans.translation.truncate();
+            spawn_cop(
+                &mut cmd,
+                kind,
+                position,
+                &arena,
+                &obstacles,
+                &config,
+                &asset_pool,
+                &mut rng,
+            );
+        }
+    }
+}
+
+fn spawn_cop(
+    cmd: &mut Commands,
+    kind: CopKind,
+    position: Vec2,
+    arena: &Arena,
+    obstacles: &[Rect],
+    config: &GameConfig,
+    asset_pool: &AssetPool,
+    rng: &mut GameRng,
+) {
+    let size = config.cop_types.get(kind).hitbox.size();
+    let goal = random_free_position(arena, obstacles, size, rng);
+    cmd.spawn((
+        Enemy::new(kind, goal, config, rng),
+        Path::default(),
+        TransformBundle {
+            local: Transform {
+                translation: position.extend(0.),
+                ..default()
+            },
+            ..default()
+        },
+        Velocity::default(),
+        Collider::from(config.cop_types.get(kind).hitbox),
+        VisibilityBundle::default(),
+    ))
+    .with_children(|cmd| {
+        cmd.spawn(Svg2dBundle {
+            svg: asset_pool.cops.get(&kind).cloned().unwrap_or_default(),
+            transform: Transform {
+                scale: Vec3 {
+                    x: 1.5,
+                    y: 1.5,
+                    ..default()
+                },
+                translation: Vec3 {
+                    x: -25.,
+                    y: 25.,
+                    ..default()
+                },
+                ..default()
+            },
+            ..default()
+        });
+    });
+}
@@ -0,0 +1,253 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    arena::Arena,
+    level::{Level, LevelObstacle},
+    obstacles::ObstacleKind,
+    pathfinding::{NavGrid, CELL_SIZE},
+};
+
+/// Thickness of the walls between rooms.
+const WALL: f32 = 20.;
+/// Height of the corridor through the middle of the level, between the centers of its walls.
+const CORRIDOR: f32 = 160.;
+/// Width of the gaps in walls.
+const DOOR: f32 = 120.;
+/// Space kept free between furniture and the walls of its room, so there is always a way around.
+/// It grows with the clearance, so the biggest cop fits too.
+const FURNITURE_MARGIN: f32 = 60.;
+/// How close PCs and USBs may be to a cop door.
+pub const MIN_COP_DISTANCE: f32 = 320.;
+/// How close PCs and USBs may be to where the player starts.
+pub const MIN_PLAYER_DISTANCE: f32 = 160.;
+/// How close PCs and USBs may be to each other.
+const MIN_SPOT_DISTANCE: f32 = 80.;
+/// Smaller levels are made this big, so there is room for the rooms, their doors, and slots away
+/// from the cops.
+pub const MIN_LEVEL_SIZE: Vec2 = Vec2::new(800., 480.);
+
+/// Lays out a level of the given size from a seed: a corridor through the middle with cop doors
+/// at both ends, rooms above and below it with furniture, and PC slots and USB spawns in the
+/// rooms. The same seed always gives the same level. Sizes below [`MIN_LEVEL_SIZE`] are grown to
+/// it.
+///
+/// PC slots and USB spawns can all be walked to from the player start by something the size of
+/// `clearance`, and are at least [`MIN_COP_DISTANCE`] from the cop doors. Every room that has
+/// such a place gets at least one of each, and layouts without any are rolled again.
+pub fn generate_level(seed: u64, size: Vec2, clearance: Vec2) -> Level {
+    let size = size.max(MIN_LEVEL_SIZE);
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    loop {
+        let level = lay_out(size, clearance, &mut rng);
+        if !level.pc_slots.is_empty() && !level.usb_spawns.is_empty() {
+            return level;
+        }
+    }
+}
+
+/// One try of [`generate_level`], which may leave out the slots and spawns.
+fn lay_out(size: Vec2, clearance: Vec2, rng: &mut ChaCha8Rng) -> Level {
+    let (min, max) = (-size / 2., size / 2.);
+    let mut level = Level {
+        name: "random".to_string(),
+        size,
+        player_start: Vec2::ZERO,
+        obstacles: Vec::new(),
+        pc_slots: Vec::new(),
+        usb_spawns: Vec::new(),
+        cop_doors: vec![
+            Vec2::new(min.x + clearance.x, 0.),
+            Vec2::new(max.x - clearance.x, 0.),
+        ],
+        ..default()
+    };
+
+    // Rooms above and below the corridor, each with a door to it, and sometimes one to the
+    // room next to it.
+    let mut rooms = Vec::new();
+    let max_rooms = ((size.x / 400.) as usize).max(2);
+    for (y0, y1, wall_y) in [
+        (CORRIDOR / 2., max.y, CORRIDOR / 2.),
+        (min.y, -CORRIDOR / 2., -CORRIDOR / 2.),
+    ] {
+        let count = rng.gen_range(2..=max_rooms);
+        let width = size.x / count as f32;
+        // every room keeps space for a door, even when both of its walls move in
+        let jitter = (width / 6.).min((width - 2. * DOOR - 1.) / 2.).max(0.);
+        let mut xs = vec![min.x];
+        for i in 1..count {
+            let offset = if 0. < jitter {
+                rng.gen_range(-jitter..jitter)
+            } else {
+                0.
+            };
+            xs.push(min.x + width * i as f32 + offset);
+        }
+        xs.push(max.x);
+
+        let mut doors = Vec::new();
+        for pair in xs.windows(2) {
+            rooms.push(Rect::new(pair[0], y0, pair[1], y1));
+            doors.push(rng.gen_range(pair[0] + DOOR..pair[1] - DOOR));
+        }
+        level
+            .obstacles
+            .extend(wall(min.x, max.x, &doors, |along| Vec2::new(along, wall_y)));
+
+        for &x in &xs[1..count] {
+            let doors = if rng.gen_bool(0.5) {
+                vec![(y0 + y1) / 2.]
+            } else {
+                Vec::new()
+            };
+            level
+                .obstacles
+                .extend(wall(y0, y1, &doors, |along| Vec2::new(x, along)));
+        }
+    }
+
+    for room in rooms.iter() {
+        let margin = FURNITURE_MARGIN.max(clearance.max_element() + CELL_SIZE);
+        let inner = shrink(*room, Vec2::splat(WALL / 2. + margin));
+        for _ in 0..rng.gen_range(1..=3) {
+            let (kind, size) = if rng.gen_bool(0.6) {
+                let size = Vec2::new(140., 60.);
+                (
+                    ObstacleKind::Desk,
+                    if rng.gen() {
+                        size
+                    } else {
+                        Vec2::new(size.y, size.x)
+                    },
+                )
+            } else {
+                (
+                    ObstacleKind::ServerRack,
+                    Vec2::new(60., rng.gen_range(100.0..180.)),
+                )
+            };
+            let area = shrink(inner, size / 2.);
+            if area.is_empty() {
+                continue;
+            }
+            level.obstacles.push(LevelObstacle {
+                kind,
+                center: random_point(area, rng),
+                size,
+            });
+        }
+    }
+
+    // Slots and spawns, only where the player can get to, and not right next to the cops.
+    let rects: Vec<Rect> = level
+        .obstacles
+        .iter()
+        .map(|obstacle| Rect::from_center_size(obstacle.center, obstacle.size))
+        .collect();
+    let grid = NavGrid::new(&Arena::new(size), &rects, clearance);
+    let (player_start, cop_doors) = (level.player_start, level.cop_doors.clone());
+    let mut taken: Vec<Vec2> = Vec::new();
+    // how far `position` is from breaking the distance rules, negative when it breaks one
+    let slack = |position: Vec2| {
+        let door = cop_doors
+            .iter()
+            .map(|door| door.distance(position))
+            .fold(f32::INFINITY, f32::min);
+        (position.distance(player_start) - MIN_PLAYER_DISTANCE).min(door - MIN_COP_DISTANCE)
+    };
+    let mut spot = |room: Rect, required: bool, rng: &mut ChaCha8Rng| {
+        let area = shrink(room, Vec2::splat(WALL / 2.) + clearance / 2.);
+        let free = |position: Vec2, taken: &[Vec2]| {
+            let rect = Rect::from_center_size(position, clearance);
+            rects
+                .iter()
+                .all(|obstacle| obstacle.intersect(rect).is_empty())
+                && taken
+                    .iter()
+                    .all(|taken| MIN_SPOT_DISTANCE <= position.distance(*taken))
+        };
+        for _ in 0..16 {
+            let position = random_point(area, rng);
+            if free(position, &taken)
+                && 0. <= slack(position)
+                && grid.find_path(player_start, position).is_some()
+            {
+                taken.push(position);
+                return Some(position);
+            }
+        }
+        if !required {
+            return None;
+        }
+
+        // Walk the room from its center outwards, and take the first free place that keeps the
+        // distance rules and can be reached.
+        let steps = (area.size() / clearance).floor().as_ivec2();
+        let mut candidates: Vec<Vec2> = (-steps.y..=steps.y)
+            .flat_map(|y| (-steps.x..=steps.x).map(move |x| IVec2::new(x, y)))
+            .map(|step| area.center() + step.as_vec2() * clearance / 2.)
+            .filter(|position| {
+                area.contains(*position) && free(*position, &taken) && 0. <= slack(*position)
+            })
+            .collect();
+        candidates.sort_by(|a, b| {
+            a.distance(area.center())
+                .total_cmp(&b.distance(area.center()))
+        });
+        let position = candidates
+            .into_iter()
+            .find(|position| grid.find_path(player_start, *position).is_some())?;
+        taken.push(position);
+        Some(position)
+    };
+    for room in rooms.iter() {
+        for i in 0..2 {
+            level.pc_slots.extend(spot(*room, i == 0, rng));
+            level.usb_spawns.extend(spot(*room, i == 0, rng));
+        }
+    }
+
+    level
+}
+
+/// The pieces of a wall from `from` to `to`, leaving a [`DOOR`] around each of `doors`.
+/// `position` turns a distance along the wall into a point.
+fn wall(from: f32, to: f32, doors: &[f32], position: impl Fn(f32) -> Vec2) -> Vec<LevelObstacle> {
+    let mut pieces = Vec::new();
+    let mut start = from;
+    let mut doors = doors.to_vec();
+    doors.sort_by(f32::total_cmp);
+    for end in doors
+        .iter()
+        .map(|door| door - DOOR / 2.)
+        .chain(std::iter::once(to))
+    {
+        if start < end {
+            let (a, b) = (position(start), position(end));
+            pieces.push(LevelObstacle {
+                kind: ObstacleKind::Wall,
+                center: (a + b) / 2.,
+                size: (b - a).abs().max(Vec2::splat(WALL)),
+            });
+        }
+        start = end + DOOR;
+    }
+    pieces
+}
+
+/// `rect` made smaller by `margin` on each side. Empty when it doesn't fit.
+fn shrink(rect: Rect, margin: Vec2) -> Rect {
+    Rect::from_center_size(rect.center(), (rect.size() - 2. * margin).max(Vec2::ZERO))
+}
+
+fn random_point(rect: Rect, rng: &mut impl Rng) -> Vec2 {
+    if rect.is_empty() {
+        return rect.center();
+    }
+    Vec2::new(
+        rng.gen_range(rect.min.x..rect.max.x),
+        rng.gen_range(rect.min.y..rect.max.y),
+    )
+}
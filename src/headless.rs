@@ -0,0 +1,47 @@
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_text_popup::TextPopupEvent;
+use std::time::Duration;
+
+use crate::{
+    arena::Arena,
+    level::{CurrentLevel, Level},
+    movement::PHYSICS_TICK,
+    rng::GameRng,
+    SimulationPlugin, State,
+};
+
+/// The time that passes on every `App::update` of a headless app, which is one physics step.
+pub const TICK: Duration = PHYSICS_TICK;
+
+/// Builds the game without a window or rendering, for automated tests. The run starts right
+/// away, without the main menu.
+///
+/// Each call to `App::update` advances the simulation by exactly one [`TICK`], in the classic
+/// level resized to the given arena.
+pub fn headless_app(arena: Arena, seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        TransformPlugin,
+        HierarchyPlugin,
+        bevy::input::InputPlugin,
+    ))
+    .add_asset::<Mesh>()
+    .add_asset::<ColorMaterial>()
+    .add_event::<TextPopupEvent>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(TICK))
+    .insert_resource(GameRng::new(Some(seed)))
+    .insert_resource(arena)
+    .insert_resource(CurrentLevel {
+        path: None,
+        level: Level {
+            size: arena.size,
+            ..default()
+        },
+        ..default()
+    })
+    .add_plugins(SimulationPlugin)
+    .insert_resource(NextState(Some(State::InGame)));
+    app
+}
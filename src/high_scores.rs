@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::{replay::Replay, rng::GameRng, storage, Common, State};
+
+/// How many scores the table keeps.
+pub const MAX_HIGH_SCORES: usize = 10;
+
+/// Loads the high score table on startup, and adds every finished run to it.
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load())
+            .init_resource::<NewHighScore>()
+            .add_systems(
+                OnEnter(State::GameOver),
+                record_high_score.run_if(not(resource_exists::<Replay>())),
+            );
+    }
+}
+
+/// One finished run.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HighScore {
+    pub score: u32,
+    /// When the run ended, in seconds since the Unix epoch.
+    pub date: u64,
+    pub seed: u64,
+    /// How long the run lasted, not counting pauses.
+    pub length: Duration,
+}
+
+/// The best runs, highest score first. Saved to `highscores.ron` natively, and to
+/// `localStorage` on the web.
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+/// Where the last run ended up in [`HighScores`], if it made the table.
+#[derive(Resource, Default)]
+pub struct NewHighScore(pub Option<usize>);
+
+impl HighScores {
+    /// Adds a run to the table, and returns its place if it made the cut. A run only beats the
+    /// ones with a lower score, so older runs win ties.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let place = self
+            .entries
+            .iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(self.entries.len());
+        if place >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.entries.insert(place, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(place)
+    }
+
+    pub fn load() -> Self {
+        storage::load("highscores")
+    }
+
+    pub fn save(&self) {
+        storage::save("highscores", self);
+    }
+}
+
+/// Formats seconds since the Unix epoch as `YYYY-MM-DD`, in UTC.
+pub fn format_date(date: u64) -> String {
+    // days to civil date, from http://howardhinnant.github.io/date_algorithms.html
+    let z = (date / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+pub(crate) fn record_high_score(
+    common: Res<Common>,
+    rng: Res<GameRng>,
+    mut high_scores: ResMut<HighScores>,
+    mut new_high_score: ResMut<NewHighScore>,
+) {
+    new_high_score.0 = high_scores.insert(HighScore {
+        score: common.score,
+        date: storage::now(),
+        seed: rng.seed(),
+        length: common.time,
+    });
+    if new_high_score.0.is_some() {
+        high_scores.save();
+    }
+}
@@ -0,0 +1,369 @@
+use bevy::prelude::*;
+
+use crate::{
+    config::GameConfig,
+    despawn_on_end_run,
+    high_scores::{format_date, record_high_score, HighScores, NewHighScore},
+    input::{Action, Actions},
+    player::Player,
+    Common, GameSet, StartRun, State,
+};
+
+pub(crate) const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+pub(crate) const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+pub(crate) const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+const NEW_RECORD: Color = Color::rgb(1.0, 0.85, 0.2);
+const DASH_METER_SIZE: Vec2 = Vec2::new(120., 10.);
+
+/// The score, lives and dash cooldown, and the game over screen with the high score table.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_score)
+            .add_systems(
+                Update,
+                (update_score, update_lives, update_dash_meter)
+                    .after(GameSet::Popups)
+                    .run_if(in_state(State::InGame)),
+            )
+            .add_systems(OnEnter(State::InGame), spawn_dash_meter.in_set(StartRun))
+            .add_systems(Update, update_button_colors)
+            .add_systems(
+                OnEnter(State::GameOver),
+                game_over_spawn.after(record_high_score),
+            )
+            .add_systems(Update, (check_restart).run_if(in_state(State::GameOver)))
+            .add_systems(OnExit(State::GameOver), game_over_despawn);
+        despawn_on_end_run::<DashMeter>(app);
+    }
+}
+
+#[derive(Component)]
+pub struct GameOver;
+
+#[derive(Component)]
+pub struct Score;
+
+/// Shows `Common::lives`, unless `GameConfig::one_hit` is on.
+#[derive(Component)]
+pub struct Lives;
+
+/// A bar that fills up while the dash cools down.
+#[derive(Component)]
+pub struct DashMeter;
+
+#[derive(Component)]
+pub struct DashMeterFill;
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum GameOverButton {
+    Restart,
+    Menu,
+}
+
+/// A button made with [`spawn_button`], as opposed to the buttons in popups.
+#[derive(Component)]
+pub struct MenuButton;
+
+fn spawn_score(mut cmd: Commands) {
+    cmd.spawn((
+        Score,
+        Text2dBundle {
+            text: Text::from_section(
+                "no score",
+                TextStyle {
+                    font_size: 60.,
+                    ..default()
+                },
+            ),
+            transform: Transform {
+                translation: Vec3 {
+                    y: 300.,
+                    z: 100.,
+                    ..default()
+                },
+                ..default()
+            },
+            ..default()
+        },
+    ));
+    cmd.spawn((
+        Lives,
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 40.,
+                    ..default()
+                },
+            ),
+            transform: Transform {
+                translation: Vec3 {
+                    y: 250.,
+                    z: 100.,
+                    ..default()
+                },
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+fn spawn_dash_meter(mut cmd: Commands) {
+    cmd.spawn((
+        DashMeter,
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0., 0., 0., 0.6),
+                custom_size: Some(DASH_METER_SIZE),
+                ..default()
+            },
+            transform: Transform::from_xyz(0., 215., 100.),
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            DashMeterFill,
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(DASH_METER_SIZE),
+                    anchor: bevy::sprite::Anchor::CenterLeft,
+                    ..default()
+                },
+                transform: Transform::from_xyz(-DASH_METER_SIZE.x / 2., 0., 1.),
+                ..default()
+            },
+        ));
+    });
+}
+
+fn update_dash_meter(
+    q_player: Query<&Player>,
+    mut q_fill: Query<(&mut Sprite, &mut Transform), With<DashMeterFill>>,
+) {
+    let (Ok(player), Ok((mut sprite, mut trans))) =
+        (q_player.get_single(), q_fill.get_single_mut())
+    else {
+        return;
+    };
+    let ready = player.dash_cooldown.finished();
+    trans.scale.x = player.dash_cooldown.percent();
+    sprite.color = if ready {
+        Color::rgb(0.3, 0.9, 0.3)
+    } else {
+        Color::rgb(0.8, 0.8, 0.8)
+    };
+}
+
+fn update_lives(
+    common: Res<Common>,
+    config: Res<GameConfig>,
+    mut q: Query<&mut Text, With<Lives>>,
+) {
+    q.single_mut().sections.first_mut().unwrap().value = if config.one_hit {
+        String::new()
+    } else {
+        format!("lives: {}", common.lives)
+    };
+}
+
+fn update_score(common: Res<Common>, mut q: Query<&mut Text, With<Score>>) {
+    q.single_mut().sections.first_mut().unwrap().value =
+        format!("crime downloaded: {}", common.score);
+}
+
+fn game_over_despawn(mut cmd: Commands, q: Query<Entity, With<GameOver>>) {
+    for entity in q.iter() {
+        cmd.entity(entity).despawn_recursive();
+    }
+}
+
+fn game_over_spawn(
+    mut cmd: Commands,
+    high_scores: Res<HighScores>,
+    new_high_score: Res<NewHighScore>,
+) {
+    cmd.spawn((GameOver, menu_node(Color::NONE)))
+        .with_children(|parent| {
+            spawn_title(parent, "game over");
+            if new_high_score.0 == Some(0) {
+                parent.spawn(TextBundle::from_section(
+                    "new record!",
+                    TextStyle {
+                        font_size: 50.,
+                        color: NEW_RECORD,
+                        ..default()
+                    },
+                ));
+            }
+            spawn_high_score_table(parent, &high_scores, new_high_score.0);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(20.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_button(parent, "restart", GameOverButton::Restart);
+                    spawn_button(parent, "menu", GameOverButton::Menu);
+                });
+        });
+}
+
+/// One line per high score, with the one from the last run in [`NEW_RECORD`] color.
+fn spawn_high_score_table(
+    parent: &mut ChildBuilder,
+    high_scores: &HighScores,
+    highlight: Option<usize>,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Start,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for (place, entry) in high_scores.entries.iter().enumerate() {
+                let length = entry.length.as_secs();
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "{:>2}. {:>4}   {}   {}:{:02}   seed {}",
+                        place + 1,
+                        entry.score,
+                        format_date(entry.date),
+                        length / 60,
+                        length % 60,
+                        entry.seed
+                    ),
+                    TextStyle {
+                        font_size: 24.,
+                        color: if Some(place) == highlight {
+                            NEW_RECORD
+                        } else {
+                            Color::rgb(0.9, 0.9, 0.9)
+                        },
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+/// A full screen column of centered UI, for menus.
+pub(crate) fn menu_node(background: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(20.0),
+            ..default()
+        },
+        background_color: background.into(),
+        z_index: ZIndex::Global(100),
+        ..default()
+    }
+}
+
+pub(crate) fn spawn_title(parent: &mut ChildBuilder, text: &str) {
+    parent.spawn(TextBundle::from_section(
+        text,
+        TextStyle {
+            font_size: 100.,
+            ..default()
+        },
+    ));
+}
+
+/// Spawns a button in the style of the game over screen, with `action` to tell it apart.
+pub(crate) fn spawn_button(parent: &mut ChildBuilder, text: &str, action: impl Component) {
+    parent
+        .spawn((
+            action,
+            MenuButton,
+            ButtonBundle {
+                style: Style {
+                    min_width: Val::Px(150.0),
+                    height: Val::Px(65.0),
+                    border: UiRect::all(Val::Px(5.0)),
+                    padding: UiRect::horizontal(Val::Px(10.0)),
+                    // horizontally center child text
+                    justify_content: JustifyContent::Center,
+                    // vertically center child text
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                border_color: BorderColor(Color::BLACK),
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                text,
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                    ..Default::default()
+                },
+            ));
+        });
+}
+
+fn check_restart(
+    actions: Actions,
+    interaction_query: Query<(&Interaction, &GameOverButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<State>>,
+) {
+    if actions.just_pressed(Action::Restart) || actions.just_pressed(Action::Confirm) {
+        next_state.set(State::InGame);
+        return;
+    }
+    if actions.just_pressed(Action::Back) {
+        next_state.set(State::MainMenu);
+        return;
+    }
+
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction == Interaction::Pressed {
+            next_state.set(match button {
+                GameOverButton::Restart => State::InGame,
+                GameOverButton::Menu => State::MainMenu,
+            });
+        }
+    }
+}
+
+fn update_button_colors(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &mut BorderColor),
+        (Changed<Interaction>, With<MenuButton>),
+    >,
+) {
+    for (interaction, mut color, mut border_color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+                border_color.0 = Color::RED;
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+                border_color.0 = Color::WHITE;
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+                border_color.0 = Color::BLACK;
+            }
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, hash::Hash};
+
+use crate::storage;
+
+/// Reads the keyboard, mouse and gamepads into [`PlayerInput`] every frame, through
+/// [`Bindings`].
+pub struct PlayerInputPlugin;
+
+impl Plugin for PlayerInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerInput>()
+            .init_resource::<Bindings>()
+            .configure_set(PreUpdate, ReadInput.after(bevy::input::InputSystem))
+            .add_systems(PreUpdate, read_player_input.in_set(ReadInput));
+    }
+}
+
+/// Systems in `PreUpdate` that write [`PlayerInput`]. Anything that overrides it, like a
+/// replay, runs after.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReadInput;
+
+/// What the player wants to do this frame, whatever the device.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct PlayerInput {
+    /// Direction to move in, no longer than 1. A stick that is only tilted a bit gives a shorter
+    /// vector, so the thief walks slower.
+    pub movement: Vec2,
+    pub dash: bool,
+}
+
+/// Something the player can do, that can be bound to keys and buttons. Moving is split in four
+/// directions, and the left stick always moves too.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Dash,
+    Restart,
+    Pause,
+    /// Picks the default choice of a menu.
+    Confirm,
+    /// Leaves a menu, and cancels rebinding in the settings.
+    Back,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Dash,
+        Action::Restart,
+        Action::Pause,
+        Action::Confirm,
+        Action::Back,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveUp => "up",
+            Action::MoveDown => "down",
+            Action::MoveLeft => "left",
+            Action::MoveRight => "right",
+            Action::Dash => "dash",
+            Action::Restart => "restart",
+            Action::Pause => "pause",
+            Action::Confirm => "confirm",
+            Action::Back => "back",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+    pub fn is_gamepad(self) -> bool {
+        matches!(self, Binding::Gamepad(_))
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::Mouse(button) => format!("mouse {button:?}"),
+            Binding::Gamepad(button) => format!("pad {button:?}"),
+        }
+        .to_lowercase()
+    }
+}
+
+/// Which keys and buttons trigger each [`Action`]. Saved between sessions. A key or button only
+/// triggers one action.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Bindings(pub BTreeMap<Action, Vec<Binding>>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Binding::*;
+        Self(BTreeMap::from([
+            (
+                Action::MoveUp,
+                vec![
+                    Key(KeyCode::Up),
+                    Key(KeyCode::W),
+                    Gamepad(GamepadButtonType::DPadUp),
+                ],
+            ),
+            (
+                Action::MoveDown,
+                vec![
+                    Key(KeyCode::Down),
+                    Key(KeyCode::S),
+                    Gamepad(GamepadButtonType::DPadDown),
+                ],
+            ),
+            (
+                Action::MoveLeft,
+                vec![
+                    Key(KeyCode::Left),
+                    Key(KeyCode::A),
+                    Gamepad(GamepadButtonType::DPadLeft),
+                ],
+            ),
+            (
+                Action::MoveRight,
+                vec![
+                    Key(KeyCode::Right),
+                    Key(KeyCode::D),
+                    Gamepad(GamepadButtonType::DPadRight),
+                ],
+            ),
+            (
+                Action::Dash,
+                vec![Key(KeyCode::Space), Gamepad(GamepadButtonType::South)],
+            ),
+            (
+                Action::Restart,
+                vec![Key(KeyCode::R), Gamepad(GamepadButtonType::North)],
+            ),
+            (
+                Action::Pause,
+                vec![Key(KeyCode::P), Gamepad(GamepadButtonType::Start)],
+            ),
+            (
+                Action::Confirm,
+                vec![
+                    Key(KeyCode::Return),
+                    Key(KeyCode::NumpadEnter),
+                    Gamepad(GamepadButtonType::West),
+                ],
+            ),
+            (
+                Action::Back,
+                vec![
+                    Key(KeyCode::Escape),
+                    Key(KeyCode::Back),
+                    Gamepad(GamepadButtonType::East),
+                    Gamepad(GamepadButtonType::Select),
+                ],
+            ),
+        ]))
+    }
+}
+
+impl Bindings {
+    /// Loads the saved bindings. Actions that were never saved get the default bindings that no
+    /// other action uses.
+    pub fn load() -> Self {
+        let mut bindings: Self = storage::load("bindings");
+        for (action, defaults) in Self::default().0 {
+            if bindings.0.contains_key(&action) {
+                continue;
+            }
+            let free = defaults
+                .into_iter()
+                .filter(|binding| bindings.action(*binding).is_none())
+                .collect();
+            bindings.0.insert(action, free);
+        }
+        bindings
+    }
+
+    pub fn save(&self) {
+        storage::save("bindings", self);
+    }
+
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// The action `binding` triggers, if any.
+    pub fn action(&self, binding: Binding) -> Option<Action> {
+        self.0
+            .iter()
+            .find(|(_, bindings)| bindings.contains(&binding))
+            .map(|(action, _)| *action)
+    }
+
+    /// Binds `action` to `binding` instead of what it was bound to on the same kind of device, so
+    /// rebinding a key keeps the gamepad binding and the other way around. Any other action
+    /// bound to `binding` loses it.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        for bindings in self.0.values_mut() {
+            bindings.retain(|old| *old != binding);
+        }
+        let bindings = self.0.entry(action).or_default();
+        bindings.retain(|old| old.is_gamepad() != binding.is_gamepad());
+        bindings.push(binding);
+    }
+}
+
+/// The state of every [`Action`], for systems that react to the player.
+#[derive(SystemParam)]
+pub struct Actions<'w> {
+    bindings: Res<'w, Bindings>,
+    keys: Res<'w, Input<KeyCode>>,
+    mouse: Res<'w, Input<MouseButton>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, Input<GamepadButton>>,
+}
+
+impl Actions<'_> {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.check(action, false)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.check(action, true)
+    }
+
+    fn check(&self, action: Action, just: bool) -> bool {
+        self.bindings
+            .get(action)
+            .iter()
+            .any(|binding| match *binding {
+                Binding::Key(key) => input_state(&self.keys, key, just),
+                Binding::Mouse(button) => input_state(&self.mouse, button, just),
+                Binding::Gamepad(button_type) => self.gamepads.iter().any(|gamepad| {
+                    input_state(
+                        &self.gamepad_buttons,
+                        GamepadButton::new(gamepad, button_type),
+                        just,
+                    )
+                }),
+            })
+    }
+}
+
+fn input_state<T: Copy + Eq + Hash + Send + Sync + 'static>(
+    input: &Input<T>,
+    value: T,
+    just: bool,
+) -> bool {
+    if just {
+        input.just_pressed(value)
+    } else {
+        input.pressed(value)
+    }
+}
+
+pub(crate) fn read_player_input(
+    actions: Actions,
+    gamepads: Res<Gamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut input: ResMut<PlayerInput>,
+) {
+    let buttons = Vec2 {
+        x: digital_axis(
+            actions.pressed(Action::MoveLeft),
+            actions.pressed(Action::MoveRight),
+        ),
+        y: digital_axis(
+            actions.pressed(Action::MoveDown),
+            actions.pressed(Action::MoveUp),
+        ),
+    };
+
+    let mut stick = Vec2::ZERO;
+    for gamepad in gamepads.iter() {
+        let axis = |axis_type| {
+            gamepad_axes
+                .get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or_default()
+        };
+        stick += Vec2 {
+            x: axis(GamepadAxisType::LeftStickX),
+            y: axis(GamepadAxisType::LeftStickY),
+        };
+    }
+
+    *input = PlayerInput {
+        movement: if buttons != Vec2::ZERO {
+            buttons.normalize()
+        } else {
+            stick.clamp_length_max(1.)
+        },
+        dash: actions.pressed(Action::Dash),
+    };
+}
+
+fn digital_axis(negative: bool, positive: bool) -> f32 {
+    match (negative, positive) {
+        (true, false) => -1.,
+        (false, true) => 1.,
+        _ => 0.,
+    }
+}
@@ -0,0 +1,297 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::{
+    arena::Arena,
+    collision::{Collider, CollisionGrid},
+    config::GameConfig,
+    despawn_on_end_run,
+    enemies::Enemy,
+    level::CurrentLevel,
+    pathfinding::NavGrid,
+    player::Player,
+    progress::ProgressBarBundle,
+    rng::GameRng,
+    spawning::{
+        place_item, spawn_warning, Occupied, Spawn, SpawnRules, SpawnWarning, SpawnWarningAssets,
+    },
+    AssetPool, Common, GameSet, PhysicsSet, StartRun, State,
+};
+
+pub struct ItemsPlugin;
+
+impl Plugin for ItemsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Items>()
+            .add_systems(OnEnter(State::InGame), spawn_items.in_set(StartRun))
+            .add_systems(
+                FixedUpdate,
+                (pick_up_usb, insert_usb).in_set(PhysicsSet::Contacts),
+            )
+            .add_systems(
+                Update,
+                (spawn_warned_items, handle_item_events)
+                    .chain()
+                    .in_set(GameSet::SpawnItems),
+            );
+        despawn_on_end_run::<Pc>(app);
+        despawn_on_end_run::<Usb>(app);
+    }
+}
+
+#[derive(Event)]
+pub enum Items {
+    AddPcUsb,
+    ClearAll,
+}
+
+#[derive(Component, Default)]
+pub struct Pc {
+    pub has_usb: bool,
+}
+
+#[derive(Component)]
+pub struct Usb;
+
+fn spawn_items(mut w_items: EventWriter<Items>) {
+    w_items.send(Items::AddPcUsb);
+    w_items.send(Items::AddPcUsb);
+}
+
+/// Places new PCs and USBs by the [`SpawnRules`]. They appear right away when the run starts,
+/// and after a [`SpawnWarning`] later on.
+fn handle_item_events(
+    mut cmd: Commands,
+    q_items: Query<Entity, Or<(With<Pc>, With<Usb>)>>,
+    q_placed: Query<&Transform, (Or<(With<Pc>, With<Usb>)>, Without<Parent>)>,
+    q_warnings: Query<(Entity, &Transform, &SpawnWarning)>,
+    q_player: Query<&Transform, With<Player>>,
+    q_cops: Query<&Transform, With<Enemy>>,
+    mut reader: EventReader<Items>,
+    nav_grid: Res<NavGrid>,
+    arena: Res<Arena>,
+    current_level: Res<CurrentLevel>,
+    common: Res<Common>,
+    config: Res<GameConfig>,
+    (asset_pool, warning_assets): (Res<AssetPool>, Res<SpawnWarningAssets>),
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<GameRng>,
+) {
+    let cops: Vec<Vec2> = q_cops.iter().map(|t| t.translation.truncate()).collect();
+    let mut items: Vec<Vec2> = q_placed
+        .iter()
+        .chain(
+            q_warnings
+                .iter()
+                .filter(|(.., warning)| !matches!(warning.spawn, Spawn::Cop(_)))
+                .map(|(_, trans, _)| trans),
+        )
+        .map(|t| t.translation.truncate())
+        .collect();
+    let player = q_player.get_single().ok().map(|t| t.translation.truncate());
+    let rules = SpawnRules::new(&config);
+    let level = &current_level.level;
+
+    for event in reader.iter() {
+        match event {
+            Items::AddPcUsb => {
+                for (spawn, spots) in [
+                    (Spawn::Pc, &level.pc_slots),
+                    (Spawn::Usb, &level.usb_spawns),
+                ] {
+                    let occupied = Occupied {
+                        player,
+                        cops: &cops,
+                        items: &items,
+                    };
+                    let position = place_item(
+                        spots,
+                        level.player_start,
+                        &arena,
+                        &nav_grid,
+                        &rules,
+                        &occupied,
+                        &mut *rng,
+                    );
+                    items.push(position);
+                    if common.is_added() {
+                        spawn_item(
+                            &mut cmd,
+                            spawn,
+                            position,
+                            &config,
+                            &asset_pool,
+                            &mut meshes,
+                            &mut materials,
+                        );
+                    } else {
+                        spawn_warning(&mut cmd, spawn, position, &config, &warning_assets);
+                    }
+                }
+            }
+            Items::ClearAll => {
+                for entity in q_items.iter() {
+                    cmd.entity(entity).despawn_recursive();
+                }
+                for (entity, _, warning) in q_warnings.iter() {
+                    if !matches!(warning.spawn, Spawn::Cop(_)) {
+                        cmd.entity(entity).despawn_recursive();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Replaces the warnings for PCs and USBs that have run out with the items.
+fn spawn_warned_items(
+    mut cmd: Commands,
+    q_warnings: Query<(Entity, &Transform, &SpawnWarning)>,
+    config: Res<GameConfig>,
+    asset_pool: Res<AssetPool>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (entity, trans, warning) in q_warnings.iter() {
+        if !matches!(warning.spawn, Spawn::Cop(_)) && warning.timer.finished() {
+            cmd.entity(entity).despawn_recursive();
+            let position = trans.translation.truncate();
+            spawn_item(
+                &mut cmd,
+                warning.spawn,
+                position,
+                &config,
+                &asset_pool,
+                &mut meshes,
+                &mut materials,
+            );
+        }
+    }
+}
+
+fn spawn_item(
+    cmd: &mut Commands,
+    spawn: Spawn,
+    position: Vec2,
+    config: &GameConfig,
+    asset_pool: &AssetPool,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+) {
+    let (texture, scale) = match spawn {
+        Spawn::Pc => (asset_pool.pc.clone(), 0.2),
+        _ => (asset_pool.usb.clone(), 0.15),
+    };
+    let mut item = cmd.spawn(SpriteBundle {
+        texture,
+        transform: Transform {
+            translation: position.extend(0.),
+            scale: Vec3 {
+                x: scale,
+                y: scale,
+                ..default()
+            },
+            ..default()
+        },
+        ..default()
+    });
+    match spawn {
+        Spawn::Pc => item.insert((Pc::default(), Collider::from(config.pc_hitbox))),
+        _ => item.insert((Usb, Collider::from(config.usb_hitbox))),
+    };
+    item.with_children(|cmd| {
+        cmd.spawn(MaterialMesh2dBundle {
+            mesh: meshes
+                .add(shape::Quad::new(Vec2::new(50., 50.)).into())
+                .into(),
+            material: materials.add(ColorMaterial::from(Color::LIME_GREEN)),
+            ..default()
+        });
+    });
+}
+
+fn pick_up_usb(
+    mut q_usb: Query<&mut Transform, (With<Usb>, Without<Parent>)>,
+    q_player: Query<(Entity, &Children), With<Player>>,
+    q_carried: Query<(), With<Usb>>,
+    grid: Res<CollisionGrid>,
+    mut cmd: Commands,
+) {
+    let (player_entity, player_children) = q_player.single();
+    let has_usb = player_children
+        .iter()
+        .any(|entity| q_carried.contains(*entity));
+    let Some((collider, position)) = grid.get(player_entity) else {
+        return;
+    };
+    if has_usb {
+        return;
+    }
+
+    let touched = grid.overlapping(&collider, position);
+    if let Some(usb) = touched.into_iter().find(|entity| q_usb.contains(*entity)) {
+        let mut usb_transform = q_usb.get_mut(usb).unwrap();
+        usb_transform.translation.x = 30.;
+        usb_transform.translation.y = 30.;
+        cmd.entity(player_entity).push_children(&[usb]);
+    }
+}
+
+/// Puts a USB into a PC without one when they touch. A carried USB goes in when the thief
+/// touches the PC.
+fn insert_usb(
+    q_usb: Query<(Entity, Option<&Parent>), With<Usb>>,
+    mut q_pc: Query<&mut Pc>,
+    grid: Res<CollisionGrid>,
+    mut cmd: Commands,
+) {
+    for (usb_entity, parent) in q_usb.iter() {
+        let holder = parent.map_or(usb_entity, |parent| parent.get());
+        let Some((usb_collider, usb_position)) = grid.get(holder) else {
+            continue;
+        };
+        for pc_entity in grid.overlapping(&usb_collider, usb_position) {
+            let Ok(mut pc) = q_pc.get_mut(pc_entity) else {
+                continue;
+            };
+            if !pc.has_usb {
+                pc.has_usb = true;
+
+                cmd.entity(usb_entity).despawn_recursive();
+
+                let progress_bar = cmd
+                    .spawn(ProgressBarBundle {
+                        text: Text2dBundle {
+                            text: Text::from_section(
+                                "downloading...",
+                                TextStyle {
+                                    font_size: 30.,
+                                    ..default()
+                                },
+                            ),
+                            transform: Transform {
+                                translation: Vec3 {
+                                    x: 0.,
+                                    y: 250.,
+                                    z: 10.,
+                                },
+                                scale: Vec3 {
+                                    x: 4.,
+                                    y: 4.,
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .id();
+
+                cmd.entity(pc_entity).push_children(&[progress_bar]);
+
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{obstacles::ObstacleKind, Background, State};
+
+/// The level files in `assets/levels`, in the order of the level select screen.
+pub const LEVEL_PATHS: [&str; 2] = ["levels/office.level.ron", "levels/server_room.level.ron"];
+
+/// Loads the levels in [`LEVEL_PATHS`], and shows the background of the level being played.
+pub struct LevelsPlugin;
+
+impl Plugin for LevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .add_systems(Startup, load_levels)
+            .add_systems(OnEnter(State::InGame), update_background);
+    }
+}
+
+/// Everything about the place a run is played in. Positions are relative to the center of the
+/// arena.
+#[derive(Serialize, Deserialize, TypeUuid, TypePath, Clone, Debug)]
+#[uuid = "0d6f3c52-8a3e-4c1b-b7a4-5e2f9d81c6a3"]
+#[serde(default)]
+pub struct Level {
+    pub name: String,
+    /// Size of the arena.
+    pub size: Vec2,
+    /// Image drawn under the arena, relative to `assets`.
+    pub background: String,
+    pub player_start: Vec2,
+    pub obstacles: Vec<LevelObstacle>,
+    /// Where PCs are placed. PCs go anywhere when this is empty.
+    pub pc_slots: Vec<Vec2>,
+    /// Where USBs are placed. USBs go anywhere when this is empty.
+    pub usb_spawns: Vec<Vec2>,
+    /// Where cops come in. They come in at the bottom left corner when this is empty.
+    pub cop_doors: Vec<Vec2>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct LevelObstacle {
+    pub kind: ObstacleKind,
+    pub center: Vec2,
+    pub size: Vec2,
+}
+
+impl Default for Level {
+    /// The classic level: a few obstacles, with items anywhere and cops from one corner.
+    fn default() -> Self {
+        let obstacle = |kind, center: [f32; 2], size: [f32; 2]| LevelObstacle {
+            kind,
+            center: center.into(),
+            size: size.into(),
+        };
+        Self {
+            name: "classic".to_string(),
+            size: Vec2::new(1280., 720.),
+            background: "floor.jpg".to_string(),
+            player_start: Vec2::ZERO,
+            obstacles: vec![
+                obstacle(ObstacleKind::Wall, [-320., 120.], [20., 300.]),
+                obstacle(ObstacleKind::Wall, [320., -120.], [20., 300.]),
+                obstacle(ObstacleKind::Desk, [-100., -200.], [160., 60.]),
+                obstacle(ObstacleKind::Desk, [120., 220.], [160., 60.]),
+                obstacle(ObstacleKind::ServerRack, [-520., -40.], [60., 140.]),
+                obstacle(ObstacleKind::ServerRack, [520., 60.], [60., 140.]),
+            ],
+            pc_slots: Vec::new(),
+            usb_spawns: Vec::new(),
+            cop_doors: Vec::new(),
+        }
+    }
+}
+
+impl Level {
+    /// Where cops come in, when the level doesn't say.
+    pub fn cop_doors_or_corner(&self) -> Vec<Vec2> {
+        if self.cop_doors.is_empty() {
+            vec![-self.size / 2.]
+        } else {
+            self.cop_doors.clone()
+        }
+    }
+}
+
+/// The level of the current run, and the file it came from, if any.
+#[derive(Resource, Default, Clone)]
+pub struct CurrentLevel {
+    pub path: Option<String>,
+    pub level: Level,
+    /// Replace `level` with [`generate_level`](crate::generator::generate_level) every time a
+    /// run starts, seeded with the seed of the run.
+    pub generated: bool,
+}
+
+/// Handles to the levels in [`LEVEL_PATHS`], in the same order.
+#[derive(Resource, Default)]
+pub struct LevelHandles(pub Vec<Handle<Level>>);
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let level = ron::de::from_bytes::<Level>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+fn load_levels(mut cmd: Commands, asset_server: Res<AssetServer>) {
+    cmd.insert_resource(LevelHandles(
+        LEVEL_PATHS
+            .iter()
+            .map(|path| asset_server.load(*path))
+            .collect(),
+    ));
+}
+
+fn update_background(
+    current_level: Res<CurrentLevel>,
+    asset_server: Res<AssetServer>,
+    mut q_background: Query<&mut Handle<Image>, With<Background>>,
+) {
+    if !current_level.is_changed() {
+        return;
+    }
+    for mut texture in q_background.iter_mut() {
+        *texture = asset_server.load(&current_level.level.background);
+    }
+}
@@ -0,0 +1,351 @@
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+//! The game *crime download*, as a set of Bevy plugins.
+//!
+//! [`CrimeDownloadPlugin`] is the whole game, and expects `DefaultPlugins`. [`SimulationPlugin`]
+//! is only the gameplay, without anything that needs a window, and is built from the plugins in
+//! [`player`], [`enemies`], [`items`], [`progress`] and [`popups`].
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*, utils::HashMap};
+use bevy_svg::prelude::*;
+use bevy_text_popup::TextPopupPlugin;
+use std::time::Duration;
+
+pub mod arena;
+mod args;
+pub mod collision;
+pub mod config;
+pub mod debug;
+pub mod difficulty;
+pub mod enemies;
+pub mod generator;
+pub mod headless;
+pub mod high_scores;
+pub mod hud;
+pub mod input;
+pub mod items;
+pub mod level;
+pub mod menu;
+pub mod movement;
+pub mod obstacles;
+pub mod pathfinding;
+pub mod pause;
+pub mod player;
+pub mod popups;
+pub mod progress;
+pub mod replay;
+pub mod rng;
+pub mod spawning;
+mod storage;
+pub mod touch;
+
+use arena::*;
+use config::*;
+use movement::PHYSICS_TICK;
+use rng::*;
+
+/// The whole game, with rendering, UI and hot-reloaded config.
+pub struct CrimeDownloadPlugin;
+
+impl Plugin for CrimeDownloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(input::Bindings::load())
+            .add_plugins((
+                TextPopupPlugin,
+                SvgPlugin,
+                ConfigPlugin,
+                debug::DebugPlugin,
+                SimulationPlugin,
+                high_scores::HighScoresPlugin,
+                hud::HudPlugin,
+                level::LevelsPlugin,
+                menu::MenuPlugin,
+                pause::PausePlugin,
+                touch::TouchControlsPlugin,
+            ))
+            .add_systems(Startup, setup)
+            .add_systems(Update, (fit_camera_to_arena, load_cop_sprites));
+    }
+}
+
+/// The gameplay, without anything that needs a window.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<GameRng>() {
+            app.insert_resource(GameRng::new(None));
+        }
+
+        app.init_resource::<GameConfig>()
+            .init_resource::<Arena>()
+            .init_resource::<AssetPool>()
+            .init_resource::<level::CurrentLevel>()
+            .add_state::<State>()
+            .insert_resource(FixedTime::new(PHYSICS_TICK))
+            .configure_sets(
+                FixedUpdate,
+                (
+                    PhysicsSet::Steering,
+                    PhysicsSet::Movement,
+                    PhysicsSet::Collision,
+                    PhysicsSet::Broadphase,
+                    PhysicsSet::Contacts,
+                )
+                    .chain(),
+            )
+            .configure_sets(
+                Update,
+                (
+                    GameSet::Rules,
+                    GameSet::SpawnItems,
+                    GameSet::SpawnEnemies,
+                    GameSet::Progress,
+                    GameSet::Popups,
+                )
+                    .chain(),
+            )
+            .add_plugins((
+                input::PlayerInputPlugin,
+                collision::CollisionPlugin,
+                difficulty::DifficultyPlugin,
+                movement::MovementPlugin,
+                obstacles::ObstaclesPlugin,
+                pathfinding::PathfindingPlugin,
+                player::PlayerPlugin,
+                enemies::EnemiesPlugin,
+                items::ItemsPlugin,
+                progress::ProgressPlugin,
+                popups::PopupsPlugin,
+                spawning::SpawningPlugin,
+            ))
+            .configure_sets(
+                OnEnter(State::InGame),
+                (
+                    PrepareRun.run_if(not(resource_exists::<Common>())),
+                    StartRun
+                        .after(PrepareRun)
+                        .run_if(not(resource_exists::<Common>())),
+                ),
+            )
+            .add_systems(OnEnter(State::InGame), start_run.in_set(PrepareRun))
+            .add_systems(Update, tick_run_time.in_set(GameSet::Rules))
+            .add_systems(EndRun, end_run)
+            .add_systems(OnExit(State::GameOver), run_end_run);
+
+        for set in PhysicsSet::ALL {
+            app.configure_set(FixedUpdate, set.run_if(in_state(State::InGame)));
+        }
+        for set in GameSet::ALL {
+            app.configure_set(Update, set.run_if(in_state(State::InGame)));
+        }
+    }
+}
+
+/// The order of the physics systems in `FixedUpdate`, which runs every [`PHYSICS_TICK`]. They
+/// only run in `State::InGame`.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhysicsSet {
+    /// Adds the impulses from the player and the cops to their velocity.
+    Steering,
+    /// Integrates velocity, applies drag and pulls everything back inside the arena.
+    Movement,
+    /// Pushes everything out of the obstacles.
+    Collision,
+    /// Rebuilds the [`collision::CollisionGrid`] from where everything ended up.
+    Broadphase,
+    /// Reacts to what touches what, like cops catching the thief, through the grid.
+    Contacts,
+}
+
+impl PhysicsSet {
+    pub const ALL: [PhysicsSet; 5] = [
+        PhysicsSet::Steering,
+        PhysicsSet::Movement,
+        PhysicsSet::Collision,
+        PhysicsSet::Broadphase,
+        PhysicsSet::Contacts,
+    ];
+}
+
+/// The order of the gameplay systems in `Update`. They only run in `State::InGame`.
+///
+/// Every system that draws from [`GameRng`] is in its own set, so runs stay deterministic.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameSet {
+    Rules,
+    SpawnItems,
+    SpawnEnemies,
+    Progress,
+    Popups,
+}
+
+impl GameSet {
+    pub const ALL: [GameSet; 5] = [
+        GameSet::Rules,
+        GameSet::SpawnItems,
+        GameSet::SpawnEnemies,
+        GameSet::Progress,
+        GameSet::Popups,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum State {
+    #[default]
+    MainMenu,
+    Settings,
+    LevelSelect,
+    InGame,
+    Paused,
+    GameOver,
+}
+
+/// Systems in `OnEnter(State::InGame)` that set up a new run. They are skipped when the game
+/// is resumed from `State::Paused`.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StartRun;
+
+/// Runs before [`StartRun`], to reseed the rng and pick the arena and level the run is played in.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PrepareRun;
+
+/// Cleans up after a run, when leaving `State::GameOver` or restarting from the pause menu.
+#[derive(ScheduleLabel, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EndRun;
+
+/// State of the current run. Inserted when a run starts, and removed on restart.
+#[derive(Resource)]
+pub struct Common {
+    pub enemy_speed: f32,
+    pub score: u32,
+    /// Time spent in `State::InGame` this run.
+    pub time: Duration,
+    /// How hard the run is right now, from the [`difficulty::Difficulty`] and the difficulty
+    /// curve.
+    pub difficulty: difficulty::DifficultyScale,
+    /// Cops sent in by the difficulty, including the ones at the start of the run.
+    pub curve_cops: u32,
+    /// Cops sent in by the difficulty or by popups, which picks the kind of the next one from
+    /// `GameConfig::cop_order`.
+    pub cops_sent: u32,
+    /// Lives the thief has left, from `GameConfig::lives`.
+    pub lives: u32,
+}
+
+impl Default for Common {
+    fn default() -> Self {
+        Self {
+            enemy_speed: 60.,
+            score: 0,
+            time: Duration::ZERO,
+            difficulty: default(),
+            curve_cops: 0,
+            cops_sent: 0,
+            lives: 1,
+        }
+    }
+}
+
+/// The floor under the arena, showing the background of the current level.
+#[derive(Component)]
+pub struct Background;
+
+#[derive(Resource, Clone, Default)]
+pub struct AssetPool {
+    pub pc: Handle<Image>,
+    pub usb: Handle<Image>,
+    /// The sprite of every kind of cop, from `GameConfig::cop_types`.
+    pub cops: HashMap<enemies::CopKind, Handle<Svg>>,
+    pub thief: Handle<Svg>,
+}
+
+fn setup(mut cmd: Commands, asset_server: Res<AssetServer>) {
+    cmd.spawn(Camera2dBundle::default());
+
+    let asset_pool = AssetPool {
+        pc: asset_server.load("computer.png"),
+        usb: asset_server.load("usb.png"),
+        thief: asset_server.load("thief.svg"),
+        ..default()
+    };
+    cmd.insert_resource(asset_pool.clone());
+
+    cmd.spawn((
+        Background,
+        SpriteBundle {
+            texture: asset_server.load("floor.jpg"),
+            transform: Transform {
+                translation: Vec3 {
+                    z: -1.,
+                    ..default()
+                },
+                scale: Vec3 {
+                    x: 0.25,
+                    y: 0.25,
+                    ..default()
+                },
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+/// Loads the sprites in `GameConfig::cop_types`, again whenever the config changes.
+fn load_cop_sprites(
+    config: Res<GameConfig>,
+    asset_server: Res<AssetServer>,
+    mut asset_pool: ResMut<AssetPool>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    for kind in enemies::CopKind::ALL {
+        let sprite = &config.cop_types.get(kind).sprite;
+        asset_pool.cops.insert(kind, asset_server.load(sprite));
+    }
+}
+
+fn start_run(
+    mut cmd: Commands,
+    config: Res<GameConfig>,
+    mut current_level: ResMut<level::CurrentLevel>,
+    mut arena: ResMut<Arena>,
+    mut rng: ResMut<GameRng>,
+) {
+    cmd.insert_resource(Common {
+        lives: config.lives.max(1),
+        ..default()
+    });
+    rng.start_run(config.seed);
+    if current_level.generated {
+        let size = current_level.level.size;
+        current_level.level = generator::generate_level(rng.seed(), size, config.clearance());
+    }
+    if arena.size != current_level.level.size {
+        *arena = Arena::new(current_level.level.size);
+    }
+}
+
+fn tick_run_time(mut common: ResMut<Common>, time: Res<Time>) {
+    common.time += time.delta();
+}
+
+fn end_run(mut cmd: Commands) {
+    cmd.remove_resource::<Common>();
+}
+
+pub(crate) fn run_end_run(world: &mut World) {
+    world.run_schedule(EndRun);
+}
+
+/// Despawns every entity with `T`.
+pub(crate) fn despawn_all<T: Component>(mut cmd: Commands, q: Query<Entity, With<T>>) {
+    for entity in q.iter() {
+        cmd.entity(entity).despawn_recursive();
+    }
+}
+
+/// Registers `despawn_all::<T>` to run at the end of every run.
+pub(crate) fn despawn_on_end_run<T: Component>(app: &mut App) {
+    app.add_systems(EndRun, despawn_all::<T>);
+}
@@ -0,0 +1,395 @@
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    window::{PrimaryWindow, WindowMode},
+};
+
+use crate::{
+    despawn_all,
+    difficulty::Difficulty,
+    hud::{menu_node, spawn_button, spawn_title},
+    input::{Action, Actions, Binding, Bindings},
+    level::{CurrentLevel, Level, LevelHandles, LEVEL_PATHS},
+    State,
+};
+
+/// The title screen, the level select screen, and the settings screen where actions can be
+/// rebound.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_systems(OnEnter(State::MainMenu), main_menu_spawn)
+            .add_systems(OnExit(State::MainMenu), despawn_all::<MainMenu>)
+            .add_systems(Update, main_menu_buttons.run_if(in_state(State::MainMenu)))
+            .add_systems(OnEnter(State::LevelSelect), level_select_spawn)
+            .add_systems(OnExit(State::LevelSelect), despawn_all::<LevelSelect>)
+            .add_systems(
+                Update,
+                level_select_buttons.run_if(in_state(State::LevelSelect)),
+            )
+            .add_systems(OnEnter(State::Settings), settings_spawn)
+            .add_systems(OnExit(State::Settings), despawn_all::<SettingsMenu>)
+            .add_systems(
+                Update,
+                (settings_buttons, update_binding_labels)
+                    .chain()
+                    .run_if(in_state(State::Settings)),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct MainMenu;
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum MainMenuButton {
+    Start,
+    Settings,
+    Quit,
+}
+
+#[derive(Component)]
+pub struct LevelSelect;
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum LevelButton {
+    /// The level built into the game.
+    Classic,
+    /// An index into [`LevelHandles`].
+    File(usize),
+    /// A new level from [`generate_level`](crate::generator::generate_level) every run.
+    Generated,
+    /// Switches to the next [`Difficulty`].
+    Difficulty,
+    Back,
+}
+
+#[derive(Component)]
+pub struct SettingsMenu;
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum SettingsButton {
+    Fullscreen,
+    Bind(Action),
+    ResetBindings,
+    Back,
+}
+
+/// The action waiting for a new binding, after its button in the settings was clicked.
+#[derive(Resource, Default)]
+pub struct Rebinding(pub Option<Action>);
+
+fn main_menu_spawn(mut cmd: Commands) {
+    cmd.spawn((MainMenu, menu_node(Color::BLACK)))
+        .with_children(|parent| {
+            spawn_title(parent, "crime download");
+            spawn_button(parent, "start", MainMenuButton::Start);
+            spawn_button(parent, "settings", MainMenuButton::Settings);
+            // closing the tab is how you quit on the web
+            #[cfg(not(target_arch = "wasm32"))]
+            spawn_button(parent, "quit", MainMenuButton::Quit);
+        });
+}
+
+fn main_menu_buttons(
+    actions: Actions,
+    interaction_query: Query<(&Interaction, &MainMenuButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<State>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if actions.just_pressed(Action::Confirm) {
+        next_state.set(State::LevelSelect);
+        return;
+    }
+
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            MainMenuButton::Start => next_state.set(State::LevelSelect),
+            MainMenuButton::Settings => next_state.set(State::Settings),
+            MainMenuButton::Quit => exit.send(AppExit),
+        }
+    }
+}
+
+/// Lists the classic level and every level file that is loaded.
+fn level_select_spawn(
+    mut cmd: Commands,
+    level_handles: Option<Res<LevelHandles>>,
+    levels: Res<Assets<Level>>,
+    difficulty: Res<Difficulty>,
+) {
+    cmd.spawn((LevelSelect, menu_node(Color::BLACK)))
+        .with_children(|parent| {
+            spawn_title(parent, "choose a level");
+            spawn_button(parent, &Level::default().name, LevelButton::Classic);
+            let handles = level_handles.iter().flat_map(|handles| handles.0.iter());
+            for (i, handle) in handles.enumerate() {
+                if let Some(level) = levels.get(handle) {
+                    spawn_button(parent, &level.name, LevelButton::File(i));
+                }
+            }
+            spawn_button(parent, "random", LevelButton::Generated);
+            spawn_button(
+                parent,
+                &difficulty_label(*difficulty),
+                LevelButton::Difficulty,
+            );
+            spawn_button(parent, "back", LevelButton::Back);
+        });
+}
+
+/// [`Action::Confirm`] plays the last level again.
+fn level_select_buttons(
+    actions: Actions,
+    interaction_query: Query<(&Interaction, &LevelButton, &Children), Changed<Interaction>>,
+    mut q_text: Query<&mut Text>,
+    level_handles: Option<Res<LevelHandles>>,
+    levels: Res<Assets<Level>>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut difficulty: ResMut<Difficulty>,
+    mut next_state: ResMut<NextState<State>>,
+) {
+    if actions.just_pressed(Action::Confirm) {
+        next_state.set(State::InGame);
+        return;
+    }
+    if actions.just_pressed(Action::Back) {
+        next_state.set(State::MainMenu);
+        return;
+    }
+
+    for (interaction, button, children) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            LevelButton::Classic => {
+                *current_level = CurrentLevel::default();
+                next_state.set(State::InGame);
+            }
+            LevelButton::File(i) => {
+                let Some(handle) = level_handles.as_ref().and_then(|handles| handles.0.get(*i))
+                else {
+                    continue;
+                };
+                let Some(level) = levels.get(handle) else {
+                    continue;
+                };
+                *current_level = CurrentLevel {
+                    path: LEVEL_PATHS.get(*i).map(|path| path.to_string()),
+                    level: level.clone(),
+                    ..default()
+                };
+                next_state.set(State::InGame);
+            }
+            LevelButton::Generated => {
+                *current_level = CurrentLevel {
+                    generated: true,
+                    ..default()
+                };
+                next_state.set(State::InGame);
+            }
+            LevelButton::Difficulty => {
+                *difficulty = difficulty.next();
+                let mut texts = q_text.iter_many_mut(children);
+                while let Some(mut text) = texts.fetch_next() {
+                    text.sections[0].value = difficulty_label(*difficulty);
+                }
+            }
+            LevelButton::Back => next_state.set(State::MainMenu),
+        }
+    }
+}
+
+fn difficulty_label(difficulty: Difficulty) -> String {
+    format!("difficulty: {}", difficulty.name())
+}
+
+fn fullscreen_label(mode: WindowMode) -> &'static str {
+    match mode {
+        WindowMode::Windowed => "fullscreen: off",
+        _ => "fullscreen: on",
+    }
+}
+
+/// The action, its first key or mouse binding, and its first gamepad binding.
+fn binding_label(action: Action, bindings: &Bindings, rebinding: &Rebinding) -> String {
+    if rebinding.0 == Some(action) {
+        return format!("{}: press a button", action.name());
+    }
+
+    let bindings = bindings.get(action);
+    let first = |gamepad: bool| {
+        bindings
+            .iter()
+            .find(|binding| binding.is_gamepad() == gamepad)
+            .map(|binding| binding.name())
+    };
+    let names: Vec<_> = [first(false), first(true)].into_iter().flatten().collect();
+    if names.is_empty() {
+        format!("{}: none", action.name())
+    } else {
+        format!("{}: {}", action.name(), names.join(" / "))
+    }
+}
+
+fn settings_spawn(
+    mut cmd: Commands,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    bindings: Res<Bindings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    let mode = q_window
+        .get_single()
+        .map(|window| window.mode)
+        .unwrap_or(WindowMode::Windowed);
+    rebinding.0 = None;
+
+    cmd.spawn((SettingsMenu, menu_node(Color::BLACK)))
+        .with_children(|parent| {
+            spawn_title(parent, "settings");
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        max_width: Val::Px(1200.0),
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        column_gap: Val::Px(20.0),
+                        row_gap: Val::Px(20.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for action in Action::ALL {
+                        spawn_button(
+                            parent,
+                            &binding_label(action, &bindings, &rebinding),
+                            SettingsButton::Bind(action),
+                        );
+                    }
+                });
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(20.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_button(parent, fullscreen_label(mode), SettingsButton::Fullscreen);
+                    spawn_button(parent, "reset controls", SettingsButton::ResetBindings);
+                    spawn_button(parent, "back", SettingsButton::Back);
+                });
+        });
+}
+
+/// The first key, mouse button or gamepad button pressed this frame.
+fn just_pressed_binding(
+    keyboard_input: &Input<KeyCode>,
+    mouse_input: &Input<MouseButton>,
+    gamepad_buttons: &Input<GamepadButton>,
+) -> Option<Binding> {
+    keyboard_input
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            mouse_input
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Mouse(*button))
+        })
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Gamepad(button.button_type))
+        })
+}
+
+fn settings_buttons(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    interaction_query: Query<(&Interaction, &SettingsButton, &Children), Changed<Interaction>>,
+    mut q_text: Query<&mut Text>,
+    mut q_window: Query<&mut Window, With<PrimaryWindow>>,
+    mut bindings: ResMut<Bindings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut next_state: ResMut<NextState<State>>,
+) {
+    // while waiting for a binding, the next press is the binding, and a press of back cancels
+    // unless it is back being rebound
+    if let Some(action) = rebinding.0 {
+        if let Some(binding) = just_pressed_binding(&keyboard_input, &mouse_input, &gamepad_buttons)
+        {
+            if action == Action::Back || bindings.action(binding) != Some(Action::Back) {
+                bindings.rebind(action, binding);
+                bindings.save();
+            }
+            rebinding.0 = None;
+        }
+        return;
+    }
+
+    let pressed = just_pressed_binding(&keyboard_input, &mouse_input, &gamepad_buttons);
+    if pressed.is_some_and(|binding| bindings.action(binding) == Some(Action::Back)) {
+        next_state.set(State::MainMenu);
+        return;
+    }
+
+    for (interaction, button, children) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            SettingsButton::Fullscreen => {
+                let Ok(mut window) = q_window.get_single_mut() else {
+                    continue;
+                };
+                window.mode = match window.mode {
+                    WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+                    _ => WindowMode::Windowed,
+                };
+                let mut texts = q_text.iter_many_mut(children);
+                while let Some(mut text) = texts.fetch_next() {
+                    text.sections[0].value = fullscreen_label(window.mode).to_string();
+                }
+            }
+            SettingsButton::Bind(action) => rebinding.0 = Some(*action),
+            SettingsButton::ResetBindings => {
+                *bindings = Bindings::default();
+                bindings.save();
+            }
+            SettingsButton::Back => next_state.set(State::MainMenu),
+        }
+    }
+}
+
+fn update_binding_labels(
+    bindings: Res<Bindings>,
+    rebinding: Res<Rebinding>,
+    q_buttons: Query<(&SettingsButton, &Children)>,
+    mut q_text: Query<&mut Text>,
+) {
+    if !bindings.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+
+    for (button, children) in q_buttons.iter() {
+        let SettingsButton::Bind(action) = button else {
+            continue;
+        };
+        let mut texts = q_text.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = binding_label(*action, &bindings, &rebinding);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use std::time::Duration;
+
+use crate::{arena::Arena, config::GameConfig, PhysicsSet};
+
+/// The length of one physics step. Impulses, drag and bound pulls are applied once per step, so
+/// movement is the same at any frame rate.
+pub const PHYSICS_TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+pub struct MovementPlugin;
+
+impl Plugin for MovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (apply_velocity, pull_inside_bounds)
+                .chain()
+                .in_set(PhysicsSet::Movement),
+        );
+    }
+}
+
+#[derive(Component, Default, Copy, Clone, Debug)]
+pub struct Velocity(pub Vec2);
+
+fn apply_velocity(
+    mut query: Query<(&mut Transform, &mut Velocity)>,
+    fixed_time: Res<FixedTime>,
+    config: Res<GameConfig>,
+) {
+    const MIN_VEL: f32 = 0.1;
+    let dt = fixed_time.period.as_secs_f32();
+
+    for (mut trans, mut vel) in query.iter_mut() {
+        trans.translation.x += vel.0.x * dt;
+        trans.translation.y += vel.0.y * dt;
+        vel.0 = if MIN_VEL < vel.0.length() {
+            vel.0 * config.drag
+        } else {
+            Vec2::ZERO
+        }
+    }
+}
+
+fn pull_inside_bounds(
+    mut query: Query<(&Transform, &mut Velocity)>,
+    arena: Res<Arena>,
+    config: Res<GameConfig>,
+) {
+    let (min, max) = (arena.min(), arena.max());
+
+    for (
+        Transform {
+            translation: Vec3 { x, y, .. },
+            ..
+        },
+        mut vel,
+    ) in query.iter_mut()
+    {
+        if max.x < *x {
+            vel.0.x -= config.pull_velocity;
+        }
+        if *x < min.x {
+            vel.0.x += config.pull_velocity;
+        }
+        if max.y < *y {
+            vel.0.y -= config.pull_velocity;
+        }
+        if *y < min.y {
+            vel.0.y += config.pull_velocity;
+        }
+    }
+}
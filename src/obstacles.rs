@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arena::Arena, collision::Collider, despawn_on_end_run, level::CurrentLevel, movement::Velocity,
+    PhysicsSet, StartRun, State,
+};
+
+/// Walls, desks and server racks. They block movement, and the cops' line of sight.
+pub struct ObstaclesPlugin;
+
+impl Plugin for ObstaclesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(State::InGame), spawn_obstacles.in_set(StartRun))
+            .add_systems(
+                FixedUpdate,
+                collide_with_obstacles.in_set(PhysicsSet::Collision),
+            );
+        despawn_on_end_run::<Obstacle>(app);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObstacleKind {
+    Wall,
+    Desk,
+    ServerRack,
+}
+
+impl ObstacleKind {
+    pub fn color(self) -> Color {
+        match self {
+            ObstacleKind::Wall => Color::rgb(0.35, 0.35, 0.4),
+            ObstacleKind::Desk => Color::rgb(0.45, 0.3, 0.2),
+            ObstacleKind::ServerRack => Color::rgb(0.1, 0.12, 0.15),
+        }
+    }
+}
+
+/// A solid box, centered on the entity's `Transform`.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Obstacle {
+    pub kind: ObstacleKind,
+    pub size: Vec2,
+}
+
+impl Obstacle {
+    pub fn rect(&self, trans: &Transform) -> Rect {
+        Rect::from_center_size(trans.translation.truncate(), self.size)
+    }
+}
+
+fn spawn_obstacles(mut cmd: Commands, current_level: Res<CurrentLevel>) {
+    for obstacle in current_level.level.obstacles.iter() {
+        spawn_obstacle(&mut cmd, obstacle.kind, obstacle.center, obstacle.size);
+    }
+}
+
+pub fn spawn_obstacle(cmd: &mut Commands, kind: ObstacleKind, center: Vec2, size: Vec2) {
+    cmd.spawn((
+        Obstacle { kind, size },
+        SpriteBundle {
+            sprite: Sprite {
+                color: kind.color(),
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_translation(center.extend(1.)),
+            ..default()
+        },
+    ));
+}
+
+/// Whether nothing blocks the straight line from `from` to `to`.
+pub fn line_of_sight(from: Vec2, to: Vec2, obstacles: &[Rect]) -> bool {
+    obstacles
+        .iter()
+        .all(|obstacle| !segment_hits_rect(from, to, *obstacle))
+}
+
+/// Slab test: clips the segment against both pairs of sides, and checks something is left.
+fn segment_hits_rect(from: Vec2, to: Vec2, rect: Rect) -> bool {
+    let dir = to - from;
+    let (mut enter, mut exit) = (0f32, 1f32);
+    for (start, delta, min, max) in [
+        (from.x, dir.x, rect.min.x, rect.max.x),
+        (from.y, dir.y, rect.min.y, rect.max.y),
+    ] {
+        if delta == 0. {
+            if start < min || max < start {
+                return false;
+            }
+            continue;
+        }
+        let (t0, t1) = ((min - start) / delta, (max - start) / delta);
+        enter = enter.max(t0.min(t1));
+        exit = exit.min(t0.max(t1));
+        if exit < enter {
+            return false;
+        }
+    }
+    true
+}
+
+/// A random position in the arena where a box of `size` doesn't overlap any obstacle. Gives up
+/// after a few tries, and returns the last position.
+pub fn random_free_position(
+    arena: &Arena,
+    obstacles: &[Rect],
+    size: Vec2,
+    rng: &mut impl Rng,
+) -> Vec2 {
+    let mut position = arena.random_position(rng);
+    for _ in 0..32 {
+        let rect = Rect::from_center_size(position, size);
+        if obstacles
+            .iter()
+            .all(|obstacle| obstacle.intersect(rect).is_empty())
+        {
+            break;
+        }
+        position = arena.random_position(rng);
+    }
+    position
+}
+
+/// Pushes everything that moves out of the obstacles its [`Collider`] walked into, and stops it
+/// from moving further in.
+fn collide_with_obstacles(
+    mut q_movers: Query<(&mut Transform, &mut Velocity, &Collider), Without<Obstacle>>,
+    q_obstacles: Query<(&Transform, &Obstacle)>,
+) {
+    for (mut trans, mut vel, collider) in q_movers.iter_mut() {
+        for (obstacle_trans, obstacle) in q_obstacles.iter() {
+            let rect = obstacle.rect(obstacle_trans);
+            let Some(push) = collider.push_out(trans.translation.truncate(), rect) else {
+                continue;
+            };
+            trans.translation += push.extend(0.);
+            let normal = push.normalize_or_zero();
+            let into = vel.0.dot(normal).min(0.);
+            vel.0 -= normal * into;
+        }
+    }
+}
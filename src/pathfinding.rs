@@ -0,0 +1,301 @@
+use bevy::prelude::*;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use crate::{
+    arena::Arena,
+    config::GameConfig,
+    level::CurrentLevel,
+    obstacles::{line_of_sight, Obstacle},
+    GameSet, PhysicsSet,
+};
+
+/// Side of a [`NavGrid`] cell.
+pub const CELL_SIZE: f32 = 20.;
+
+/// A* on a grid over the arena, for cops to walk around obstacles.
+pub struct PathfindingPlugin;
+
+impl Plugin for PathfindingPlugin {
+    fn build(&self, app: &mut App) {
+        // also before spawning, which can come before the first physics step of a run
+        app.init_resource::<NavGrid>()
+            .add_systems(FixedUpdate, update_nav_grid.before(PhysicsSet::Steering))
+            .add_systems(Update, update_nav_grid.before(GameSet::Rules));
+    }
+}
+
+/// Which cells of the arena a cop can stand in. Obstacles are grown by half of the clearance,
+/// usually [`GameConfig::clearance`], so a path through free cells never clips a corner.
+#[derive(Resource, Default)]
+pub struct NavGrid {
+    origin: Vec2,
+    size: IVec2,
+    blocked: Vec<bool>,
+    /// The grown obstacles, slightly shrunk, to check if a straight line is walkable.
+    walls: Vec<Rect>,
+    /// Bumped on every rebuild, so cached paths know they are stale.
+    generation: u32,
+    /// The cells that can be walked to from the player start, kept from the last rebuild.
+    reachable: HashSet<IVec2>,
+}
+
+impl NavGrid {
+    pub fn new(arena: &Arena, obstacles: &[Rect], clearance: Vec2) -> Self {
+        let size = (arena.size / CELL_SIZE).ceil().as_ivec2();
+        let grown: Vec<Rect> = obstacles
+            .iter()
+            .map(|rect| Rect::from_center_size(rect.center(), rect.size() + clearance))
+            .collect();
+        let mut grid = Self {
+            origin: arena.min(),
+            size,
+            blocked: Vec::new(),
+            walls: grown
+                .iter()
+                .map(|rect| Rect::from_center_size(rect.center(), rect.size() - 2.))
+                .collect(),
+            generation: 0,
+            reachable: HashSet::new(),
+        };
+        grid.blocked = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| IVec2::new(x, y)))
+            .map(|cell| {
+                let center = grid.center(cell);
+                grown.iter().any(|rect| rect.contains(center))
+            })
+            .collect();
+        grid
+    }
+
+    pub fn cell(&self, position: Vec2) -> IVec2 {
+        ((position - self.origin) / CELL_SIZE)
+            .floor()
+            .as_ivec2()
+            .clamp(IVec2::ZERO, (self.size - 1).max(IVec2::ZERO))
+    }
+
+    pub fn center(&self, cell: IVec2) -> Vec2 {
+        self.origin + (cell.as_vec2() + 0.5) * CELL_SIZE
+    }
+
+    pub fn is_blocked(&self, cell: IVec2) -> bool {
+        if cell.x < 0 || cell.y < 0 || self.size.x <= cell.x || self.size.y <= cell.y {
+            return true;
+        }
+        self.blocked[(cell.y * self.size.x + cell.x) as usize]
+    }
+
+    /// Whether a cop can walk straight from `from` to `to`.
+    pub fn clear_line(&self, from: Vec2, to: Vec2) -> bool {
+        line_of_sight(from, to, &self.walls)
+    }
+
+    /// The points to walk through to get from `start` to `goal`, ending with `goal`. Straight
+    /// stretches are merged, so there are only points at corners. `None` when `goal` can't be
+    /// reached.
+    pub fn find_path(&self, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        if self.clear_line(start, goal) {
+            return Some(vec![goal]);
+        }
+
+        let (start_cell, goal_cell) = (self.cell(start), self.cell(goal));
+        let heuristic = |cell: IVec2| {
+            let d = (cell - goal_cell).abs();
+            10 * d.x.max(d.y) + 4 * d.x.min(d.y)
+        };
+
+        let mut open = BinaryHeap::from([(Reverse(heuristic(start_cell)), start_cell.to_array())]);
+        let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+        let mut cost = HashMap::from([(start_cell, 0)]);
+
+        while let Some((_, cell)) = open.pop() {
+            let cell = IVec2::from_array(cell);
+            if cell == goal_cell {
+                let mut cells = vec![cell];
+                while let Some(previous) = came_from.get(cells.last().unwrap()) {
+                    cells.push(*previous);
+                }
+                cells.reverse();
+                return Some(self.smooth(start, goal, &cells));
+            }
+
+            for (next, diagonal) in self.steps(cell) {
+                if next != goal_cell && self.is_blocked(next) {
+                    continue;
+                }
+                let next_cost = cost[&cell] + if diagonal { 14 } else { 10 };
+                if next_cost < *cost.get(&next).unwrap_or(&i32::MAX) {
+                    cost.insert(next, next_cost);
+                    came_from.insert(next, cell);
+                    open.push((Reverse(next_cost + heuristic(next)), next.to_array()));
+                }
+            }
+        }
+        None
+    }
+
+    /// The grid, remembering which cells can be walked to from `start` for
+    /// [`NavGrid::can_reach`].
+    pub fn with_reachable_from(mut self, start: Vec2) -> Self {
+        self.reachable = self.reachable_cells(start);
+        self
+    }
+
+    /// Whether `position` can be walked to from the start given to
+    /// [`NavGrid::with_reachable_from`]. Never when it wasn't given one.
+    pub fn can_reach(&self, position: Vec2) -> bool {
+        self.reachable.contains(&self.cell(position))
+    }
+
+    /// Every free cell that can be walked to from `start`, along the same steps as
+    /// [`NavGrid::find_path`].
+    pub fn reachable_cells(&self, start: Vec2) -> HashSet<IVec2> {
+        let start = self.cell(start);
+        let mut reached = HashSet::from([start]);
+        let mut open = vec![start];
+        while let Some(cell) = open.pop() {
+            for (next, _) in self.steps(cell) {
+                if !self.is_blocked(next) && reached.insert(next) {
+                    open.push(next);
+                }
+            }
+        }
+        reached
+    }
+
+    /// The cells next to `cell`, and whether they are diagonal to it. Diagonals that would cut
+    /// the corner of a blocked cell are left out.
+    fn steps(&self, cell: IVec2) -> impl Iterator<Item = (IVec2, bool)> + '_ {
+        [
+            IVec2::X,
+            IVec2::NEG_X,
+            IVec2::Y,
+            IVec2::NEG_Y,
+            IVec2::ONE,
+            IVec2::NEG_ONE,
+            IVec2::new(1, -1),
+            IVec2::new(-1, 1),
+        ]
+        .into_iter()
+        .filter_map(move |step| {
+            let diagonal = step.x != 0 && step.y != 0;
+            // no cutting corners
+            let cuts_corner = diagonal
+                && (self.is_blocked(cell + IVec2::new(step.x, 0))
+                    || self.is_blocked(cell + IVec2::new(0, step.y)));
+            (!cuts_corner).then_some((cell + step, diagonal))
+        })
+    }
+
+    /// Skips every cell that can be seen past, from the last point that was kept.
+    fn smooth(&self, start: Vec2, goal: Vec2, cells: &[IVec2]) -> Vec<Vec2> {
+        let mut points: Vec<Vec2> = cells.iter().map(|cell| self.center(*cell)).collect();
+        *points.last_mut().unwrap() = goal;
+
+        let mut path = Vec::new();
+        let mut from = start;
+        let mut i = 0;
+        while i < points.len() {
+            let mut furthest = i;
+            while furthest + 1 < points.len() && self.clear_line(from, points[furthest + 1]) {
+                furthest += 1;
+            }
+            from = points[furthest];
+            path.push(from);
+            i = furthest + 1;
+        }
+        path
+    }
+}
+
+/// How far the goal of a [`Path`] can move before the path is searched again.
+pub const REPLAN_DISTANCE: f32 = 3. * CELL_SIZE;
+
+/// The cached way of a cop to its goal. It is only searched again when the goal moves more than
+/// [`REPLAN_DISTANCE`] from where it was searched for, when the goal can't be walked to straight
+/// from the last corner, or when the grid changes. Until then the last leg of the path is moved
+/// onto the goal.
+#[derive(Component, Default)]
+pub struct Path {
+    /// Where the goal was when the path was searched.
+    planned_goal: Option<Vec2>,
+    generation: u32,
+    /// The points left to walk through, last one first.
+    waypoints: Vec<Vec2>,
+}
+
+impl Path {
+    /// The point to walk towards to reach `goal`, or `None` when within `margin` of it.
+    pub fn next_waypoint(
+        &mut self,
+        position: Vec2,
+        goal: Vec2,
+        grid: &NavGrid,
+        margin: f32,
+    ) -> Option<Vec2> {
+        if position.distance(goal) <= margin {
+            return None;
+        }
+
+        let moved = self
+            .planned_goal
+            .map_or(f32::INFINITY, |planned| planned.distance(goal));
+        // the last leg is bent onto the moved goal, so it has to stay walkable
+        let last_leg_blocked = 0. < moved && {
+            let from = self.waypoints.get(1).copied().unwrap_or(position);
+            !grid.clear_line(from, goal)
+        };
+        if REPLAN_DISTANCE < moved || last_leg_blocked || self.generation != grid.generation {
+            self.planned_goal = Some(goal);
+            self.generation = grid.generation;
+            self.waypoints = grid.find_path(position, goal).unwrap_or_else(|| vec![goal]);
+            self.waypoints.reverse();
+        } else if let Some(last) = self.waypoints.first_mut() {
+            *last = goal;
+        }
+
+        while 1 < self.waypoints.len()
+            && position.distance(*self.waypoints.last().unwrap()) < CELL_SIZE / 2.
+        {
+            self.waypoints.pop();
+        }
+        self.waypoints.last().copied()
+    }
+
+    /// The points left to walk through, in order.
+    pub fn waypoints(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.waypoints.iter().rev().copied()
+    }
+}
+
+fn update_nav_grid(
+    mut grid: ResMut<NavGrid>,
+    q_obstacles: Query<(&Transform, &Obstacle)>,
+    q_added: Query<(), Added<Obstacle>>,
+    mut removed: RemovedComponents<Obstacle>,
+    arena: Res<Arena>,
+    current_level: Res<CurrentLevel>,
+    config: Res<GameConfig>,
+) {
+    let removed = removed.iter().count() != 0;
+    if q_added.is_empty()
+        && !removed
+        && !arena.is_changed()
+        && !current_level.is_changed()
+        && !config.is_changed()
+    {
+        return;
+    }
+
+    let obstacles: Vec<Rect> = q_obstacles
+        .iter()
+        .map(|(trans, obstacle)| obstacle.rect(trans))
+        .collect();
+    let generation = grid.generation + 1;
+    *grid = NavGrid::new(&arena, &obstacles, config.clearance())
+        .with_reachable_from(current_level.level.player_start);
+    grid.generation = generation;
+}
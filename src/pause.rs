@@ -0,0 +1,78 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    despawn_all,
+    hud::{menu_node, spawn_button, spawn_title},
+    input::{Action, Actions},
+    replay::Replay,
+    run_end_run, State,
+};
+
+/// Pauses the game on [`Action::Pause`], and shows the pause menu. [`Action::Back`] resumes too.
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            toggle_pause
+                .run_if(in_state(State::InGame).or_else(in_state(State::Paused)))
+                .run_if(not(resource_exists::<Replay>())),
+        )
+        .add_systems(Update, pause_menu_buttons.run_if(in_state(State::Paused)))
+        .add_systems(OnEnter(State::Paused), pause_menu_spawn)
+        .add_systems(OnExit(State::Paused), despawn_all::<PauseMenu>);
+    }
+}
+
+#[derive(Component)]
+pub struct PauseMenu;
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum PauseButton {
+    Resume,
+    Restart,
+    Quit,
+}
+
+fn toggle_pause(
+    actions: Actions,
+    state: Res<bevy::ecs::schedule::State<State>>,
+    mut next_state: ResMut<NextState<State>>,
+) {
+    let paused = *state.get() == State::Paused;
+    if actions.just_pressed(Action::Pause) || (paused && actions.just_pressed(Action::Back)) {
+        next_state.set(if paused { State::InGame } else { State::Paused });
+    }
+}
+
+fn pause_menu_spawn(mut cmd: Commands) {
+    cmd.spawn((PauseMenu, menu_node(Color::rgba(0., 0., 0., 0.5))))
+        .with_children(|parent| {
+            spawn_title(parent, "paused");
+            spawn_button(parent, "resume", PauseButton::Resume);
+            spawn_button(parent, "restart", PauseButton::Restart);
+            spawn_button(parent, "quit", PauseButton::Quit);
+        });
+}
+
+fn pause_menu_buttons(
+    mut cmd: Commands,
+    interaction_query: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<State>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            PauseButton::Resume => next_state.set(State::InGame),
+            PauseButton::Restart => {
+                cmd.add(run_end_run);
+                next_state.set(State::InGame);
+            }
+            PauseButton::Quit => exit.send(AppExit),
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_svg::prelude::*;
+
+use crate::{
+    arena::Arena,
+    collision::{Collider, CollisionGrid},
+    config::GameConfig,
+    despawn_on_end_run,
+    enemies::Enemy,
+    input::PlayerInput,
+    items::Usb,
+    level::CurrentLevel,
+    movement::Velocity,
+    pathfinding::NavGrid,
+    rng::GameRng,
+    spawning::{pick_respawn, SpawnRules},
+    AssetPool, Common, GameSet, PhysicsSet, StartRun, State,
+};
+
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_after_images)
+            .add_systems(OnEnter(State::InGame), spawn_player.in_set(StartRun))
+            .add_systems(FixedUpdate, steer_player.in_set(PhysicsSet::Steering))
+            .add_systems(FixedUpdate, catch_player.in_set(PhysicsSet::Contacts))
+            .add_systems(
+                FixedUpdate,
+                spawn_after_images
+                    .after(PhysicsSet::Collision)
+                    .run_if(in_state(State::InGame)),
+            )
+            .add_systems(
+                Update,
+                (blink_player, fade_after_images)
+                    .chain()
+                    .in_set(GameSet::Rules),
+            );
+        despawn_on_end_run::<Player>(app);
+        despawn_on_end_run::<AfterImage>(app);
+    }
+}
+
+#[derive(Component)]
+pub struct Player {
+    /// Runs from the start of a dash. The thief can dash again when it finishes.
+    pub dash_cooldown: Timer,
+    /// Runs while the thief is dashing.
+    pub dash_duration: Timer,
+    /// Where the current or last dash went.
+    pub dash_direction: Vec2,
+    /// Whether dash was held the last physics step, so holding it only dashes once.
+    pub dash_held: bool,
+    pub has_usb: bool,
+    /// Runs after losing a life. The thief can't be caught until it finishes.
+    pub grace: Timer,
+}
+
+impl Player {
+    pub fn new(config: &GameConfig) -> Self {
+        let finished = |seconds| {
+            let mut timer = Timer::from_seconds(seconds, TimerMode::Once);
+            timer.tick(timer.duration());
+            timer
+        };
+        Self {
+            dash_cooldown: finished(config.dash_cooldown),
+            dash_duration: finished(config.dash_time),
+            dash_direction: Vec2::X,
+            dash_held: false,
+            has_usb: false,
+            grace: finished(config.capture_grace),
+        }
+    }
+
+    pub fn dashing(&self) -> bool {
+        !self.dash_duration.finished()
+    }
+
+    /// Whether cops can catch the thief right now.
+    pub fn catchable(&self, config: &GameConfig) -> bool {
+        self.grace.finished() && !(config.dash_invulnerable && self.dashing())
+    }
+}
+
+/// A fading copy of the thief, left behind while dashing.
+#[derive(Component)]
+pub struct AfterImage(pub Timer);
+
+/// How many steps an [`AfterImage`] fades in.
+const AFTER_IMAGE_FADE_STEPS: usize = 5;
+
+/// The circle every [`AfterImage`] is drawn with, and a material for every step of fading, most
+/// faded first. They are shared, so dashing doesn't make new assets.
+#[derive(Resource)]
+struct AfterImageAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<Handle<ColorMaterial>>,
+}
+
+fn setup_after_images(
+    mut cmd: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    cmd.insert_resource(AfterImageAssets {
+        mesh: meshes.add(shape::Circle::new(1.).into()),
+        materials: (1..=AFTER_IMAGE_FADE_STEPS)
+            .map(|step| {
+                let alpha = 0.5 * step as f32 / AFTER_IMAGE_FADE_STEPS as f32;
+                materials.add(ColorMaterial::from(Color::rgba(0.6, 0.8, 1., alpha)))
+            })
+            .collect(),
+    });
+}
+
+fn spawn_player(
+    mut cmd: Commands,
+    asset_pool: Res<AssetPool>,
+    current_level: Res<CurrentLevel>,
+    config: Res<GameConfig>,
+) {
+    cmd.spawn((
+        Player::new(&config),
+        TransformBundle::from_transform(Transform::from_translation(
+            current_level.level.player_start.extend(0.),
+        )),
+        Velocity::default(),
+        Collider::from(config.player_hitbox),
+        ComputedVisibility::default(),
+        Visibility::Visible,
+    ))
+    .with_children(|cmd| {
+        cmd.spawn(Svg2dBundle {
+            svg: asset_pool.thief.clone(),
+            transform: Transform {
+                translation: Vec3 {
+                    x: -25.,
+                    y: 25.,
+                    z: 10.,
+                },
+                scale: Vec3 {
+                    x: 0.1,
+                    y: 0.1,
+                    ..default()
+                },
+                ..default()
+            },
+            origin: Origin::Center,
+            ..default()
+        });
+    });
+}
+
+fn steer_player(
+    input: Res<PlayerInput>,
+    mut query: Query<(&mut Velocity, &mut Player)>,
+    fixed_time: Res<FixedTime>,
+    config: Res<GameConfig>,
+) {
+    let (mut vel, mut player) = query.single_mut();
+
+    player.dash_cooldown.tick(fixed_time.period);
+    player.dash_duration.tick(fixed_time.period);
+
+    let pressed = input.dash && !player.dash_held;
+    player.dash_held = input.dash;
+    if pressed && player.dash_cooldown.finished() {
+        if input.movement != Vec2::ZERO {
+            player.dash_direction = input.movement.normalize();
+        }
+        player.dash_duration = Timer::from_seconds(config.dash_time, TimerMode::Once);
+        player.dash_cooldown = Timer::from_seconds(config.dash_cooldown, TimerMode::Once);
+    }
+    if input.movement != Vec2::ZERO && !player.dashing() {
+        player.dash_direction = input.movement.normalize();
+    }
+
+    // a dash goes straight at full speed, whatever the stick says
+    vel.0 += if player.dashing() {
+        player.dash_direction * config.player_speed * config.dash_multiplier
+    } else {
+        input.movement * config.player_speed
+    };
+}
+
+/// Ends the run when a cop catches the thief on the last life, or in `GameConfig::one_hit` mode.
+/// Otherwise it costs a life: the thief drops the USB and comes back at the start or the PC slot
+/// of the level furthest from the cops, with `GameConfig::capture_grace` to get away. USB spawns
+/// are left out, so it doesn't come back right next to a new USB.
+fn catch_player(
+    mut next_state: ResMut<NextState<State>>,
+    mut query_player: Query<(
+        Entity,
+        &mut Player,
+        &mut Transform,
+        &mut Velocity,
+        &Children,
+    )>,
+    mut query_usb: Query<&mut Transform, (With<Usb>, Without<Player>)>,
+    query_enemies: Query<&Transform, (With<Enemy>, Without<Player>, Without<Usb>)>,
+    grid: Res<CollisionGrid>,
+    mut common: ResMut<Common>,
+    nav_grid: Res<NavGrid>,
+    current_level: Res<CurrentLevel>,
+    arena: Res<Arena>,
+    config: Res<GameConfig>,
+    mut rng: ResMut<GameRng>,
+    fixed_time: Res<FixedTime>,
+    mut cmd: Commands,
+) {
+    let (entity, mut player, mut trans, mut vel, children) = query_player.single_mut();
+    player.grace.tick(fixed_time.period);
+    if !player.catchable(&config) {
+        return;
+    }
+    let Some((collider, position)) = grid.get(entity) else {
+        return;
+    };
+    let touched = grid.overlapping(&collider, position);
+    if !touched
+        .into_iter()
+        .any(|entity| query_enemies.contains(entity))
+    {
+        return;
+    }
+
+    common.lives = common.lives.saturating_sub(1);
+    if config.one_hit || common.lives == 0 {
+        common.lives = 0;
+        next_state.set(State::GameOver);
+        return;
+    }
+
+    for child in children.iter() {
+        if let Ok(mut usb_trans) = query_usb.get_mut(*child) {
+            usb_trans.translation = trans.translation.truncate().extend(0.);
+            cmd.entity(*child).remove_parent();
+        }
+    }
+
+    let level = &current_level.level;
+    let cops: Vec<Vec2> = query_enemies
+        .iter()
+        .map(|t| t.translation.truncate())
+        .collect();
+    let respawn = pick_respawn(
+        level.player_start,
+        &level.pc_slots,
+        &arena,
+        &nav_grid,
+        &SpawnRules::new(&config),
+        &cops,
+        &mut *rng,
+    );
+    trans.translation = respawn.extend(trans.translation.z);
+    vel.0 = Vec2::ZERO;
+    player.grace = Timer::from_seconds(config.capture_grace, TimerMode::Once);
+}
+
+/// Leaves an [`AfterImage`] where the thief is, every physics step of a dash.
+fn spawn_after_images(
+    mut cmd: Commands,
+    query: Query<(&Player, &Transform, &Collider)>,
+    assets: Res<AfterImageAssets>,
+    config: Res<GameConfig>,
+) {
+    let (player, trans, collider) = query.single();
+    if !player.dashing() {
+        return;
+    }
+    let radius = collider.shape.size().x / 2.;
+    cmd.spawn((
+        AfterImage(Timer::from_seconds(
+            config.after_image_time,
+            TimerMode::Once,
+        )),
+        MaterialMesh2dBundle {
+            mesh: assets.mesh.clone().into(),
+            material: assets.materials.last().unwrap().clone(),
+            transform: Transform {
+                translation: (trans.translation.truncate() + collider.offset).extend(5.),
+                scale: Vec3::new(radius, radius, 1.),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+fn fade_after_images(
+    mut cmd: Commands,
+    mut query: Query<(Entity, &mut AfterImage, &mut Handle<ColorMaterial>)>,
+    assets: Res<AfterImageAssets>,
+    time: Res<Time>,
+) {
+    for (entity, mut after_image, mut material) in query.iter_mut() {
+        after_image.0.tick(time.delta());
+        if after_image.0.finished() {
+            cmd.entity(entity).despawn_recursive();
+            continue;
+        }
+        let step = (after_image.0.percent_left() * AFTER_IMAGE_FADE_STEPS as f32) as usize;
+        let faded = &assets.materials[step.min(AFTER_IMAGE_FADE_STEPS - 1)];
+        if *material != *faded {
+            *material = faded.clone();
+        }
+    }
+}
+
+/// Makes the thief blink while it can't be caught.
+fn blink_player(mut query: Query<(&Player, &mut Visibility)>) {
+    let (player, mut visibility) = query.single_mut();
+    let hidden = !player.grace.finished() && (player.grace.elapsed_secs() * 10.) as u32 % 2 == 1;
+    let wanted = if hidden {
+        Visibility::Hidden
+    } else {
+        Visibility::Visible
+    };
+    if *visibility != wanted {
+        *visibility = wanted;
+    }
+}
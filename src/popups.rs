@@ -2,18 +2,45 @@ use bevy::prelude::*;
 use bevy_text_popup::{
     TextPopupButton, TextPopupEvent, TextPopupLocation,
 };
-use rand::seq::IteratorRandom;
+use rand::{seq::IteratorRandom, Rng};
+use serde::{Deserialize, Serialize};
 
-#[derive(Event)]
+use crate::{
+    config::GameConfig,
+    difficulty::next_cop,
+    enemies::{AddEnemy, Enemy},
+    player::Player,
+    replay::{Recorder, Replay},
+    Common, EndRun, GameSet,
+};
+
+pub struct PopupsPlugin;
+
+impl Plugin for PopupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PopupCommand>()
+            .init_resource::<QueuedPopups>()
+            .add_systems(Update, queue_popup_commands.before(GameSet::Popups))
+            .add_systems(Update, handle_popup_events.in_set(GameSet::Popups))
+            .add_systems(EndRun, clear_queued_popups);
+    }
+}
+
+/// Popup buttons pressed while the game isn't running, for when it runs again. Popups stay
+/// clickable while paused, so closing one there still has its effect.
+#[derive(Resource, Default)]
+struct QueuedPopups(Vec<PopupCommand>);
+
+#[derive(Event, Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum PopupCommand {
     AddCop,
     CopsTargetPlayer,
     IncreaseCopSpeed,
 }
 
-pub fn insert_random_popup(writer: &mut EventWriter<TextPopupEvent>) {
+pub fn insert_random_popup(writer: &mut EventWriter<TextPopupEvent>, rng: &mut impl Rng) {
 
-    let random_location = ||[    
+    let mut random_location = ||[    
         TextPopupLocation::TopLeft,
         TextPopupLocation::Top,
         TextPopupLocation::TopRight,
@@ -23,7 +50,7 @@ pub fn insert_random_popup(writer: &mut EventWriter<TextPopupEvent>) {
         TextPopupLocation::BottomLeft,
         TextPopupLocation::Bottom,
         TextPopupLocation::BottomRight,
-    ].into_iter().choose(&mut rand::thread_rng()).unwrap();
+    ].into_iter().choose(rng).unwrap();
     
     let list: Vec<_> = [TextPopupEvent {
         content: "Do you want to allow us to enhance the experience by increasing your safety and security?".to_string(),
@@ -148,6 +175,55 @@ pub fn insert_random_popup(writer: &mut EventWriter<TextPopupEvent>) {
     }]
     .into();
 
-    let mut rng = rand::thread_rng();
-    writer.send(list.into_iter().choose(&mut rng).unwrap());
+    writer.send(list.into_iter().choose(rng).unwrap());
+}
+
+fn queue_popup_commands(mut reader: EventReader<PopupCommand>, mut queue: ResMut<QueuedPopups>) {
+    queue.0.extend(reader.iter().copied());
+}
+
+fn clear_queued_popups(mut queue: ResMut<QueuedPopups>) {
+    queue.0.clear();
+}
+
+fn handle_popup_events(
+    q_player: Query<&Transform, With<Player>>,
+    mut q_enemy: Query<&mut Enemy>,
+    mut w_enemy: EventWriter<AddEnemy>,
+    mut common: ResMut<Common>,
+    mut queue: ResMut<QueuedPopups>,
+    config: Res<GameConfig>,
+    replay: Option<Res<Replay>>,
+    mut recorder: Option<ResMut<Recorder>>,
+) {
+    let events: Vec<PopupCommand> = match replay {
+        Some(replay) => {
+            queue.0.clear();
+            replay
+                .frame()
+                .map(|frame| frame.popups.clone())
+                .unwrap_or_default()
+        }
+        None => std::mem::take(&mut queue.0),
+    };
+
+    for event in events {
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record_popup(event);
+        }
+        match event {
+            PopupCommand::AddCop => {
+                w_enemy.send(AddEnemy(next_cop(&mut common, &config)));
+            }
+            PopupCommand::CopsTargetPlayer => {
+                let player_trans = q_player.single();
+                for mut enemy in q_enemy.iter_mut() {
+                    enemy.alert(player_trans.translation.truncate(), &config);
+                }
+            }
+            PopupCommand::IncreaseCopSpeed => {
+                common.enemy_speed += config.enemy_speed_increase;
+            }
+        }
+    }
 }
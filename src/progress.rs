@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use bevy_text_popup::TextPopupEvent;
+use rand::Rng;
+use std::time::Duration;
+
+use crate::{
+    config::GameConfig,
+    despawn_on_end_run,
+    items::{Items, Pc},
+    popups::insert_random_popup,
+    rng::GameRng,
+    Common, GameSet,
+};
+
+pub struct ProgressPlugin;
+
+impl Plugin for ProgressPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_progress_and_spawn_popups.in_set(GameSet::Progress),
+        );
+        despawn_on_end_run::<ProgressBar>(app);
+    }
+}
+
+/// Time a download takes for every percent, before the difficulty.
+const DOWNLOAD_STEP: Duration = Duration::from_millis(250);
+
+#[derive(Component)]
+pub struct ProgressBar {
+    pub timer: Timer,
+    pub timer_popups: Timer,
+    pub progress: u32,
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self {
+            timer: Timer::new(DOWNLOAD_STEP, TimerMode::Repeating),
+            timer_popups: Timer::from_seconds(1., TimerMode::Repeating),
+            progress: 0,
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct ProgressBarBundle {
+    pub text: Text2dBundle,
+    pub tag: ProgressBar,
+}
+
+impl Default for ProgressBarBundle {
+    fn default() -> Self {
+        Self {
+            text: Text2dBundle {
+                text: Text::from_section(
+                    "downloading...",
+                    TextStyle {
+                        font_size: 30.,
+                        ..default()
+                    },
+                ),
+                transform: Transform::default(),
+                ..default()
+            },
+            tag: ProgressBar::default(),
+        }
+    }
+}
+
+fn update_progress_and_spawn_popups(
+    mut cmd: Commands,
+    mut q: Query<Option<(Entity, &mut ProgressBar, &mut Text, &Parent)>>,
+    time: Res<Time>,
+    q_pc: Query<Entity, With<Pc>>,
+    mut writer: EventWriter<TextPopupEvent>,
+    mut w_items: EventWriter<Items>,
+    mut common: ResMut<Common>,
+    config: Res<GameConfig>,
+    mut rng: ResMut<GameRng>,
+) {
+    let step = DOWNLOAD_STEP.mul_f32(common.difficulty.download_time);
+    for (_, mut p, mut text, parent) in q.iter_mut().flatten() {
+        if p.timer.duration() != step {
+            p.timer.set_duration(step);
+        }
+        if p.timer.tick(time.delta()).just_finished() {
+            if 100 == p.progress {
+                common.score += 1;
+                let pc_entity = q_pc.get(parent.get()).unwrap();
+                cmd.entity(pc_entity).despawn_recursive();
+
+                w_items.send(Items::AddPcUsb);
+                if rng.gen::<f32>() < config.extra_pc_chance {
+                    w_items.send(Items::AddPcUsb);
+                }
+            } else {
+                p.progress += 1;
+            }
+        }
+
+        if p.timer_popups.tick(time.delta()).just_finished()
+            && rng.gen::<f32>() < config.popup_chance_per_sec * common.difficulty.popup_chance
+        {
+            insert_random_popup(&mut writer, &mut *rng);
+        }
+
+        text.sections.first_mut().unwrap().value = format!("download {}", p.progress);
+    }
+}
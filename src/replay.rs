@@ -0,0 +1,258 @@
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    config::GameConfig,
+    difficulty::Difficulty,
+    input::{PlayerInput, ReadInput},
+    level::{CurrentLevel, Level},
+    popups::PopupCommand,
+    rng::GameRng,
+    Common, StartRun, State,
+};
+
+/// Everything that happened during one frame of `State::InGame`.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Frame {
+    pub delta: Duration,
+    pub input: PlayerInput,
+    pub popups: Vec<PopupCommand>,
+}
+
+/// A recorded run. Together with the seed and the level, the frames are enough to play the run
+/// again.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Run {
+    pub seed: u64,
+    /// The whole level, so generated levels and edited level files play back the same.
+    #[serde(default)]
+    pub level: Level,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    /// The config when the run started. Older recordings without one play back with the
+    /// defaults.
+    #[serde(default)]
+    pub config: Option<GameConfig>,
+    pub score: u32,
+    pub frames: Vec<Frame>,
+}
+
+/// Records the run to a file, enabled with `--record <file>`.
+#[derive(Resource)]
+pub struct Recorder {
+    path: PathBuf,
+    run: Run,
+    popups: Vec<PopupCommand>,
+}
+
+impl Recorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            run: Run::default(),
+            popups: Vec::new(),
+        }
+    }
+
+    pub fn record_popup(&mut self, command: PopupCommand) {
+        self.popups.push(command);
+    }
+}
+
+/// Plays a recorded run instead of reading the keyboard and gamepads, enabled with
+/// `--replay <file>`.
+#[derive(Resource)]
+pub struct Replay {
+    run: Run,
+    cursor: usize,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Self {
+        let file = std::fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("could not read replay '{}': {err}", path.display()));
+        let run: Run = ron::from_str(&file)
+            .unwrap_or_else(|err| panic!("could not parse replay '{}': {err}", path.display()));
+        Self { run, cursor: 0 }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.run.seed
+    }
+
+    pub fn frame(&self) -> Option<&Frame> {
+        self.run.frames.get(self.cursor)
+    }
+}
+
+/// Records runs to a file, or plays a recorded run back.
+pub enum ReplayPlugin {
+    Off,
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl ReplayPlugin {
+    /// Reads the `--record <file>` and `--replay <file>` flags.
+    pub fn from_args() -> Self {
+        if let Some(path) = crate::args::value("--replay") {
+            Self::Replay(path.into())
+        } else if let Some(path) = crate::args::value("--record") {
+            Self::Record(path.into())
+        } else {
+            Self::Off
+        }
+    }
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match self {
+            Self::Off => return,
+            Self::Replay(path) => {
+                let replay = Replay::load(path);
+                info!(
+                    "replaying '{}', {} frames",
+                    path.display(),
+                    replay.run.frames.len()
+                );
+                let level = CurrentLevel {
+                    level: replay.run.level.clone(),
+                    ..default()
+                };
+                let config = replay.run.config.clone().unwrap_or_else(|| {
+                    warn!("the replay has no config, using the defaults");
+                    GameConfig::default()
+                });
+                app.insert_resource(GameRng::new(Some(replay.seed())))
+                    .insert_resource(config)
+                    .insert_resource(level)
+                    .insert_resource(replay.run.difficulty)
+                    .insert_resource(replay)
+                    .insert_resource(NextState(Some(State::InGame)));
+            }
+            Self::Record(path) => {
+                app.insert_resource(Recorder::new(path.clone()));
+            }
+        }
+
+        app.add_systems(
+            First,
+            replay_time
+                .before(bevy::time::TimeSystem)
+                .run_if(resource_exists::<Replay>()),
+        )
+        .add_systems(
+            PreUpdate,
+            replay_input
+                .after(ReadInput)
+                .run_if(resource_exists::<Replay>()),
+        )
+        .add_systems(
+            Last,
+            (
+                record_frame.run_if(resource_exists::<Recorder>()),
+                advance_replay.run_if(resource_exists::<Replay>()),
+            )
+                .run_if(in_state(State::InGame)),
+        )
+        .add_systems(OnEnter(State::InGame), start_recording.in_set(StartRun))
+        .add_systems(OnEnter(State::GameOver), (save_recording, finish_replay));
+    }
+}
+
+fn replay_time(replay: Res<Replay>, mut strategy: ResMut<TimeUpdateStrategy>) {
+    if let Some(frame) = replay.frame() {
+        *strategy = TimeUpdateStrategy::ManualDuration(frame.delta);
+    }
+}
+
+/// Replaces what the player did with what was recorded.
+fn replay_input(replay: Res<Replay>, mut input: ResMut<PlayerInput>) {
+    *input = replay.frame().map(|frame| frame.input).unwrap_or_default();
+}
+
+fn advance_replay(mut replay: ResMut<Replay>) {
+    replay.cursor += 1;
+}
+
+fn start_recording(recorder: Option<ResMut<Recorder>>, config: Res<GameConfig>) {
+    if let Some(mut recorder) = recorder {
+        recorder.run = Run {
+            config: Some(config.clone()),
+            ..default()
+        };
+        recorder.popups.clear();
+    }
+}
+
+fn record_frame(mut recorder: ResMut<Recorder>, time: Res<Time>, input: Res<PlayerInput>) {
+    let frame = Frame {
+        delta: time.delta(),
+        input: *input,
+        popups: std::mem::take(&mut recorder.popups),
+    };
+    recorder.run.frames.push(frame);
+}
+
+fn save_recording(
+    recorder: Option<ResMut<Recorder>>,
+    common: Res<Common>,
+    rng: Res<GameRng>,
+    current_level: Res<CurrentLevel>,
+    difficulty: Res<Difficulty>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    recorder.run.seed = rng.seed();
+    recorder.run.level = current_level.level.clone();
+    recorder.run.difficulty = *difficulty;
+    recorder.run.score = common.score;
+
+    let result = ron::to_string(&recorder.run)
+        .map_err(|err| err.to_string())
+        .and_then(|run| std::fs::write(&recorder.path, run).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => info!(
+            "recorded {} frames to '{}'",
+            recorder.run.frames.len(),
+            recorder.path.display()
+        ),
+        Err(err) => error!(
+            "could not save recording to '{}': {err}",
+            recorder.path.display()
+        ),
+    }
+}
+
+/// Checks that the replay ended the same way as the recording, then hands control back to the
+/// player.
+fn finish_replay(
+    mut cmd: Commands,
+    replay: Option<Res<Replay>>,
+    common: Res<Common>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+) {
+    let Some(replay) = replay else {
+        return;
+    };
+
+    let (frames, score) = (replay.cursor, common.score);
+    if frames == replay.run.frames.len() && score == replay.run.score {
+        info!("replay matched the recording: score {score} after {frames} frames");
+    } else {
+        warn!(
+            "replay diverged: score {score} after {frames} frames, recorded score {} after {} frames",
+            replay.run.score,
+            replay.run.frames.len()
+        );
+    }
+
+    cmd.remove_resource::<Replay>();
+    *strategy = TimeUpdateStrategy::Automatic;
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// The only source of randomness in the simulation.
+///
+/// Every run is reseeded when it starts, so the same seed and the same inputs play out the same
+/// way. The seed is taken from `--seed <n>`, then from `seed` in the config, and is otherwise
+/// random.
+#[derive(Resource)]
+pub struct GameRng {
+    fixed_seed: Option<u64>,
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(fixed_seed: Option<u64>) -> Self {
+        let seed = fixed_seed.unwrap_or_default();
+        Self {
+            fixed_seed,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn from_args() -> Self {
+        let fixed_seed = crate::args::value("--seed").map(|seed| {
+            seed.parse()
+                .unwrap_or_else(|_| panic!("--seed expects a number, got '{seed}'"))
+        });
+        Self::new(fixed_seed)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn start_run(&mut self, config_seed: Option<u64>) {
+        self.seed = self.fixed_seed.or(config_seed).unwrap_or_else(rand::random);
+        self.rng = ChaCha8Rng::seed_from_u64(self.seed);
+        info!("starting run with seed {}", self.seed);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
@@ -0,0 +1,280 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    arena::Arena, config::GameConfig, despawn_on_end_run, enemies::CopKind, pathfinding::NavGrid,
+    GameSet,
+};
+
+/// Picks fair places for new PCs, USBs and cops, and warns about them before they appear.
+pub struct SpawningPlugin;
+
+impl Plugin for SpawningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_spawn_warnings)
+            .add_systems(Update, update_spawn_warnings.in_set(GameSet::Rules));
+        despawn_on_end_run::<SpawnWarning>(app);
+    }
+}
+
+/// What is about to appear where a [`SpawnWarning`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Spawn {
+    Pc,
+    Usb,
+    Cop(CopKind),
+}
+
+impl Spawn {
+    fn color(self) -> Color {
+        match self {
+            Spawn::Pc | Spawn::Usb => Color::rgba(0.2, 0.8, 0.2, 0.4),
+            Spawn::Cop(_) => Color::rgba(0.9, 0.1, 0.1, 0.4),
+        }
+    }
+
+    /// Half the size of the collider of what is coming. Its warning starts out twice as big.
+    fn radius(self, config: &GameConfig) -> f32 {
+        let shape = match self {
+            Spawn::Pc => config.pc_hitbox,
+            Spawn::Usb => config.usb_hitbox,
+            Spawn::Cop(kind) => config.cop_types.get(kind).hitbox,
+        };
+        shape.size().max_element() / 2.
+    }
+}
+
+/// The circle every [`SpawnWarning`] is drawn with, and its colors for items and for cops. They
+/// are shared, so warnings don't make new assets.
+#[derive(Resource)]
+pub struct SpawnWarningAssets {
+    mesh: Handle<Mesh>,
+    item: Handle<ColorMaterial>,
+    cop: Handle<ColorMaterial>,
+}
+
+fn setup_spawn_warnings(
+    mut cmd: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    cmd.insert_resource(SpawnWarningAssets {
+        mesh: meshes.add(shape::Circle::new(1.).into()),
+        item: materials.add(ColorMaterial::from(Spawn::Pc.color())),
+        cop: materials.add(ColorMaterial::from(Spawn::Cop(CopKind::Officer).color())),
+    });
+}
+
+/// A shrinking circle where something will appear when `timer` finishes. The items and enemies
+/// plugins replace it with the real thing.
+#[derive(Component)]
+pub struct SpawnWarning {
+    pub spawn: Spawn,
+    pub timer: Timer,
+}
+
+/// How far new things are kept from what is already there.
+#[derive(Clone, Copy, Debug)]
+pub struct SpawnRules {
+    pub player_distance: f32,
+    pub cop_distance: f32,
+    pub item_distance: f32,
+    /// Space kept free along the edges of the arena.
+    pub margin: f32,
+    /// Room kept free around new things, from [`GameConfig::clearance`].
+    pub clearance: Vec2,
+}
+
+impl SpawnRules {
+    pub fn new(config: &GameConfig) -> Self {
+        Self {
+            player_distance: config.spawn_player_distance,
+            cop_distance: config.spawn_cop_distance,
+            item_distance: config.spawn_item_distance,
+            margin: config.spawn_margin,
+            clearance: config.clearance(),
+        }
+    }
+
+    /// How much room `position` has to spare, over the closest of the rules. Negative when a
+    /// rule is broken.
+    fn slack(&self, position: Vec2, occupied: &Occupied) -> f32 {
+        let closest = |points: &[Vec2]| {
+            points
+                .iter()
+                .map(|point| point.distance(position))
+                .fold(f32::INFINITY, f32::min)
+        };
+        let player = occupied
+            .player
+            .map_or(f32::INFINITY, |player| player.distance(position));
+        (player - self.player_distance)
+            .min(closest(occupied.cops) - self.cop_distance)
+            .min(closest(occupied.items) - self.item_distance)
+    }
+}
+
+/// Where things already are.
+pub struct Occupied<'a> {
+    pub player: Option<Vec2>,
+    pub cops: &'a [Vec2],
+    /// PCs, USBs, and the warnings for the ones that are about to appear.
+    pub items: &'a [Vec2],
+}
+
+/// Where to put a new PC or USB. Tries the `spots` of the level that aren't taken yet, in a
+/// random order, or random places in the arena that the `grid` can reach from the player start
+/// when there are none. The first place that follows every rule wins. When none does, the one
+/// that breaks them the least is used.
+pub fn place_item(
+    spots: &[Vec2],
+    start: Vec2,
+    arena: &Arena,
+    grid: &NavGrid,
+    rules: &SpawnRules,
+    occupied: &Occupied,
+    rng: &mut impl Rng,
+) -> Vec2 {
+    let mut candidates: Vec<Vec2> = spots
+        .iter()
+        .copied()
+        .filter(|spot| occupied.items.iter().all(|item| 1. < item.distance(*spot)))
+        .collect();
+    candidates.shuffle(rng);
+    if candidates.is_empty() {
+        candidates = free_positions(start, arena, grid, rules, rng);
+    }
+
+    let mut best = (f32::NEG_INFINITY, Vec2::ZERO);
+    for candidate in candidates {
+        let slack = rules.slack(candidate, occupied);
+        if 0. <= slack {
+            return candidate;
+        }
+        if best.0 < slack {
+            best = (slack, candidate);
+        }
+    }
+    best.1
+}
+
+/// Random cells of the arena, away from its edges, that the `grid` can reach from the player
+/// start. Free cells are clear of the obstacles, so their centers are used. Just `start` when
+/// none of them can be reached.
+fn free_positions(
+    start: Vec2,
+    arena: &Arena,
+    grid: &NavGrid,
+    rules: &SpawnRules,
+    rng: &mut impl Rng,
+) -> Vec<Vec2> {
+    let inner = Arena::new((arena.size - 2. * rules.margin).max(rules.clearance));
+    let mut positions: Vec<Vec2> = (0..32)
+        .map(|_| grid.center(grid.cell(inner.random_position(rng))))
+        .filter(|position| grid.can_reach(*position))
+        .collect();
+    if positions.is_empty() {
+        positions.push(start);
+    }
+    positions
+}
+
+/// Which of the `doors` a new cop comes in through: a random one that is far enough from the
+/// player, or the one furthest away when none is.
+pub fn pick_cop_door(
+    doors: &[Vec2],
+    player: Option<Vec2>,
+    rules: &SpawnRules,
+    rng: &mut impl Rng,
+) -> Vec2 {
+    let Some(player) = player else {
+        return doors[rng.gen_range(0..doors.len())];
+    };
+    let fair: Vec<Vec2> = doors
+        .iter()
+        .copied()
+        .filter(|door| rules.player_distance <= door.distance(player))
+        .collect();
+    if fair.is_empty() {
+        doors
+            .iter()
+            .copied()
+            .max_by(|a, b| a.distance(player).total_cmp(&b.distance(player)))
+            .unwrap()
+    } else {
+        fair[rng.gen_range(0..fair.len())]
+    }
+}
+
+/// Where the thief comes back after being caught: whichever of `start` and `spots` is furthest
+/// from the closest cop, preferring `start` on a tie. Random places in the arena stand in for
+/// the `spots` when the level has none.
+pub fn pick_respawn(
+    start: Vec2,
+    spots: &[Vec2],
+    arena: &Arena,
+    grid: &NavGrid,
+    rules: &SpawnRules,
+    cops: &[Vec2],
+    rng: &mut impl Rng,
+) -> Vec2 {
+    let mut candidates = spots.to_vec();
+    if candidates.is_empty() {
+        candidates = free_positions(start, arena, grid, rules, rng);
+    }
+    let safety = |position: Vec2| {
+        cops.iter()
+            .map(|cop| cop.distance(position))
+            .fold(f32::INFINITY, f32::min)
+    };
+    candidates.into_iter().fold(start, |best, candidate| {
+        if safety(best) < safety(candidate) {
+            candidate
+        } else {
+            best
+        }
+    })
+}
+
+/// Shows where `spawn` will appear in `GameConfig::spawn_warning_time`.
+pub fn spawn_warning(
+    cmd: &mut Commands,
+    spawn: Spawn,
+    position: Vec2,
+    config: &GameConfig,
+    assets: &SpawnWarningAssets,
+) {
+    let radius = spawn.radius(config);
+    cmd.spawn((
+        SpawnWarning {
+            spawn,
+            timer: Timer::from_seconds(config.spawn_warning_time, TimerMode::Once),
+        },
+        MaterialMesh2dBundle {
+            mesh: assets.mesh.clone().into(),
+            material: match spawn {
+                Spawn::Pc | Spawn::Usb => assets.item.clone(),
+                Spawn::Cop(_) => assets.cop.clone(),
+            },
+            transform: Transform::from_translation(position.extend(0.5)).with_scale(Vec3::new(
+                radius * 2.,
+                radius * 2.,
+                1.,
+            )),
+            ..default()
+        },
+    ));
+}
+
+/// Ticks the warnings, and shrinks them towards the size of what is coming.
+fn update_spawn_warnings(
+    mut q_warnings: Query<(&mut SpawnWarning, &mut Transform)>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    for (mut warning, mut trans) in q_warnings.iter_mut() {
+        warning.timer.tick(time.delta());
+        let scale = warning.spawn.radius(&config) * (2. - warning.timer.percent());
+        trans.scale = Vec3::new(scale, scale, 1.);
+    }
+}
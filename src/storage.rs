@@ -0,0 +1,93 @@
+//! Small files that outlive the game, like high scores and key bindings. They are saved as RON
+//! in the data directory of the user natively, and in `localStorage` on the web.
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Reads `name`, or returns the default when it was never saved or can't be parsed.
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let Some(contents) = platform::read(name) else {
+        return T::default();
+    };
+    ron::from_str(&contents).unwrap_or_else(|err| {
+        warn!("could not parse {name}, using the defaults: {err}");
+        T::default()
+    })
+}
+
+pub fn save<T: Serialize>(name: &str, value: &T) {
+    let result = ron::to_string(value)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| platform::write(name, &contents));
+    if let Err(err) = result {
+        error!("could not save {name}: {err}");
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    platform::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use directories::ProjectDirs;
+    use std::{
+        path::PathBuf,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    /// The file in the data directory of the user, or the current directory when there is no
+    /// home to find it in.
+    fn path(name: &str) -> PathBuf {
+        let file = format!("{name}.ron");
+        match ProjectDirs::from("", "", "crime-download") {
+            Some(dirs) => dirs.data_dir().join(file),
+            None => PathBuf::from(file),
+        }
+    }
+
+    pub fn read(name: &str) -> Option<String> {
+        std::fs::read_to_string(path(name)).ok()
+    }
+
+    pub fn write(name: &str, contents: &str) -> Result<(), String> {
+        let path = path(name);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+        std::fs::write(path, contents).map_err(|err| err.to_string())
+    }
+
+    pub fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    fn key(name: &str) -> String {
+        format!("crime-download-{name}")
+    }
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn read(name: &str) -> Option<String> {
+        local_storage()?.get_item(&key(name)).ok()?
+    }
+
+    pub fn write(name: &str, contents: &str) -> Result<(), String> {
+        local_storage()
+            .ok_or("localStorage is not available")?
+            .set_item(&key(name), contents)
+            .map_err(|err| format!("{err:?}"))
+    }
+
+    pub fn now() -> u64 {
+        (js_sys::Date::now() / 1000.) as u64
+    }
+}
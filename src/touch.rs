@@ -0,0 +1,197 @@
+use bevy::{input::touch::Touches, prelude::*};
+
+use crate::{
+    input::{read_player_input, PlayerInput, ReadInput},
+    State,
+};
+
+const JOYSTICK_RADIUS: f32 = 80.;
+const KNOB_SIZE: f32 = 60.;
+const DASH_SIZE: f32 = 140.;
+
+/// A virtual joystick and dash button for phones. They show up once the screen is touched, and
+/// hide again when a key is pressed.
+pub struct TouchControlsPlugin;
+
+impl Plugin for TouchControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TouchControls>()
+            .add_systems(Startup, spawn_touch_controls)
+            .add_systems(
+                PreUpdate,
+                touch_input.in_set(ReadInput).after(read_player_input),
+            )
+            .add_systems(Update, update_touch_controls);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct TouchControls {
+    pub enabled: bool,
+    joystick: Option<Joystick>,
+}
+
+/// A finger that is steering. The joystick is centered where the finger first touched.
+struct Joystick {
+    touch: u64,
+    center: Vec2,
+    offset: Vec2,
+}
+
+#[derive(Component)]
+pub struct TouchRoot;
+
+#[derive(Component)]
+pub struct JoystickBase;
+
+#[derive(Component)]
+pub struct JoystickKnob;
+
+#[derive(Component)]
+pub struct DashButton;
+
+fn spawn_touch_controls(mut cmd: Commands) {
+    let square = |size: f32, alpha: f32| NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Px(size),
+            height: Val::Px(size),
+            ..default()
+        },
+        background_color: Color::rgba(1., 1., 1., alpha).into(),
+        ..default()
+    };
+
+    cmd.spawn((
+        TouchRoot,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(50),
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((JoystickBase, square(JOYSTICK_RADIUS * 2., 0.15)));
+        parent.spawn((JoystickKnob, square(KNOB_SIZE, 0.4)));
+
+        let mut dash = square(DASH_SIZE, 0.2);
+        dash.style.right = Val::Px(40.);
+        dash.style.bottom = Val::Px(40.);
+        dash.style.justify_content = JustifyContent::Center;
+        dash.style.align_items = AlignItems::Center;
+        parent.spawn((DashButton, dash)).with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "dash",
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                    ..default()
+                },
+            ));
+        });
+    });
+}
+
+/// Steers with the joystick and dashes when the dash button is touched, on top of the keyboard and
+/// gamepads. Touches that start on a button, like the ones in popups, are left alone.
+fn touch_input(
+    touches: Res<Touches>,
+    keyboard_input: Res<Input<KeyCode>>,
+    q_dash: Query<(&Node, &GlobalTransform), With<DashButton>>,
+    q_buttons: Query<(&Node, &GlobalTransform), With<Interaction>>,
+    mut controls: ResMut<TouchControls>,
+    mut input: ResMut<PlayerInput>,
+) {
+    if touches.any_just_pressed() {
+        controls.enabled = true;
+    } else if keyboard_input.get_just_pressed().next().is_some() {
+        controls.enabled = false;
+    }
+    if !controls.enabled {
+        controls.joystick = None;
+        return;
+    }
+
+    let over = |(node, transform): (&Node, &GlobalTransform), position: Vec2| {
+        node.logical_rect(transform).contains(position)
+    };
+    let over_dash = |position| q_dash.iter().any(|dash| over(dash, position));
+
+    if let Some(joystick) = &controls.joystick {
+        if touches.get_pressed(joystick.touch).is_none() {
+            controls.joystick = None;
+        }
+    }
+    if controls.joystick.is_none() {
+        controls.joystick = touches
+            .iter_just_pressed()
+            .find(|touch| {
+                let position = touch.position();
+                !over_dash(position) && !q_buttons.iter().any(|button| over(button, position))
+            })
+            .map(|touch| Joystick {
+                touch: touch.id(),
+                center: touch.position(),
+                offset: Vec2::ZERO,
+            });
+    }
+
+    if let Some(joystick) = &mut controls.joystick {
+        if let Some(touch) = touches.get_pressed(joystick.touch) {
+            joystick.offset =
+                (touch.position() - joystick.center).clamp_length_max(JOYSTICK_RADIUS);
+        }
+        if joystick.offset != Vec2::ZERO {
+            // screen coordinates grow downwards, the world upwards
+            input.movement = Vec2::new(joystick.offset.x, -joystick.offset.y) / JOYSTICK_RADIUS;
+        }
+    }
+    if touches.iter().any(|touch| over_dash(touch.position())) {
+        input.dash = true;
+    }
+}
+
+fn update_touch_controls(
+    controls: Res<TouchControls>,
+    state: Res<bevy::ecs::schedule::State<State>>,
+    mut q_root: Query<&mut Visibility, With<TouchRoot>>,
+    mut q_joystick: Query<
+        (&mut Style, &mut Visibility, Option<&JoystickKnob>),
+        (
+            Or<(With<JoystickBase>, With<JoystickKnob>)>,
+            Without<TouchRoot>,
+        ),
+    >,
+) {
+    let shown = |shown| {
+        if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        }
+    };
+
+    for mut visibility in q_root.iter_mut() {
+        *visibility = shown(controls.enabled && *state.get() == State::InGame);
+    }
+
+    for (mut style, mut visibility, knob) in q_joystick.iter_mut() {
+        *visibility = shown(controls.joystick.is_some());
+        let Some(joystick) = &controls.joystick else {
+            continue;
+        };
+        let (position, size) = if knob.is_some() {
+            (joystick.center + joystick.offset, KNOB_SIZE)
+        } else {
+            (joystick.center, JOYSTICK_RADIUS * 2.)
+        };
+        style.left = Val::Px(position.x - size / 2.);
+        style.top = Val::Px(position.y - size / 2.);
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use crime_download::collision::{Collider, CollisionGrid};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+#[test]
+fn shapes_overlap_by_their_outline() {
+    let square = Collider::aabb(Vec2::splat(50.));
+    let circle = Collider::circle(25.);
+
+    assert!(square.overlaps(Vec2::ZERO, &square, Vec2::new(49., 49.)));
+    assert!(!square.overlaps(Vec2::ZERO, &square, Vec2::new(51., 0.)));
+    assert!(circle.overlaps(Vec2::ZERO, &circle, Vec2::new(49., 0.)));
+    // the corners of the boxes touch, the circles don't
+    assert!(!circle.overlaps(Vec2::ZERO, &circle, Vec2::new(40., 40.)));
+    assert!(square.overlaps(Vec2::ZERO, &circle, Vec2::new(40., 0.)));
+    assert!(!square.overlaps(Vec2::ZERO, &circle, Vec2::new(44., 44.)));
+    assert!(circle.overlaps(Vec2::ZERO, &square, Vec2::new(0., -40.)));
+}
+
+#[test]
+fn offsets_move_the_collider() {
+    let shifted = Collider::circle(10.).with_offset(Vec2::new(100., 0.));
+    let target = Collider::circle(10.);
+    assert!(!shifted.overlaps(Vec2::ZERO, &target, Vec2::ZERO));
+    assert!(shifted.overlaps(Vec2::ZERO, &target, Vec2::new(105., 0.)));
+
+    let others = [
+        ("near", &target, Vec2::new(0., 0.)),
+        ("far", &target, Vec2::new(110., 0.)),
+    ];
+    assert_eq!(shifted.first_overlap(Vec2::ZERO, others), Some("far"));
+}
+
+#[test]
+fn grid_finds_the_same_overlaps_as_checking_everything() {
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    let mut grid = CollisionGrid::default();
+    let mut colliders = Vec::new();
+    for i in 0..1000 {
+        let collider = if rng.gen() {
+            Collider::circle(rng.gen_range(5.0..60.))
+        } else {
+            Collider::aabb(Vec2::new(
+                rng.gen_range(5.0..150.),
+                rng.gen_range(5.0..150.),
+            ))
+        };
+        let position = Vec2::new(rng.gen_range(-2000.0..2000.), rng.gen_range(-2000.0..2000.));
+        let entity = Entity::from_raw(i);
+        grid.insert(entity, collider, position);
+        colliders.push((entity, collider, position));
+    }
+
+    for (_, collider, position) in colliders.iter().take(100) {
+        let expected: Vec<Entity> = colliders
+            .iter()
+            .filter(|(_, other, other_position)| {
+                collider.overlaps(*position, other, *other_position)
+            })
+            .map(|(entity, ..)| *entity)
+            .collect();
+        assert_eq!(grid.overlapping(collider, *position), expected);
+    }
+    assert_eq!(
+        grid.get(Entity::from_raw(3)),
+        Some((colliders[3].1, colliders[3].2))
+    );
+}
+
+#[test]
+fn colliders_are_pushed_out_of_boxes() {
+    let wall = Rect::from_center_size(Vec2::ZERO, Vec2::new(100., 20.));
+
+    let circle = Collider::circle(25.);
+    assert_eq!(circle.push_out(Vec2::new(0., 40.), wall), None);
+    let push = circle.push_out(Vec2::new(0., 30.), wall).unwrap();
+    assert!((push - Vec2::new(0., 5.)).length() < 0.001, "{push}");
+    // round corners let the circle closer than a box
+    assert_eq!(circle.push_out(Vec2::new(70., 30.), wall), None);
+
+    let square = Collider::aabb(Vec2::splat(50.)).with_offset(Vec2::new(0., 10.));
+    assert_eq!(
+        square.push_out(Vec2::new(70., 20.), wall),
+        Some(Vec2::new(5., 0.))
+    );
+}
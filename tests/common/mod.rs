@@ -0,0 +1,35 @@
+//! Helpers shared by the integration tests. Not every test file uses all of them.
+#![allow(dead_code)]
+
+use bevy::{hierarchy::despawn_with_children_recursive, prelude::*};
+use crime_download::{enemies::Enemy, level::Level, player::Player};
+
+/// Reads a level right away, instead of through the asset server. `path` is relative to
+/// `assets`.
+pub fn load_level(path: &str) -> Level {
+    let file = std::fs::read_to_string(format!("assets/{path}"))
+        .unwrap_or_else(|err| panic!("could not read level '{path}': {err}"));
+    ron::from_str(&file).unwrap_or_else(|err| panic!("could not parse level '{path}': {err}"))
+}
+
+/// Keeps a single cop, at `offset` from the thief and looking at it.
+pub fn single_cop_at(app: &mut App, offset: Vec3) {
+    let enemies = app
+        .world
+        .query_filtered::<Entity, With<Enemy>>()
+        .iter(&app.world)
+        .collect::<Vec<_>>();
+    for enemy in &enemies[1..] {
+        despawn_with_children_recursive(&mut app.world, *enemy);
+    }
+
+    let player = app
+        .world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(&app.world)
+        .translation;
+    let mut q = app.world.query::<(&mut Transform, &mut Enemy)>();
+    let (mut trans, mut enemy) = q.single_mut(&mut app.world);
+    trans.translation = player + offset;
+    enemy.facing = -offset.truncate().normalize();
+}
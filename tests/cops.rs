@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use crime_download::{
+    arena::Arena,
+    collision::Shape,
+    config::GameConfig,
+    enemies::{CopKind, CopState, Enemy},
+    headless::headless_app,
+    items::Usb,
+    player::Player,
+};
+
+mod common;
+use common::single_cop_at;
+
+#[test]
+fn config_file_has_every_cop_type() {
+    let file = std::fs::read_to_string("assets/config.ron").unwrap();
+    let config: GameConfig = ron::from_str(&file).unwrap();
+    let defaults = GameConfig::default();
+    for kind in CopKind::ALL {
+        let (loaded, default) = (config.cop_types.get(kind), defaults.cop_types.get(kind));
+        assert_eq!(loaded.sprite, default.sprite, "{}", kind.name());
+        assert_eq!(loaded.speed, default.speed, "{}", kind.name());
+        assert_eq!(loaded.hitbox, default.hitbox, "{}", kind.name());
+    }
+    assert_eq!(config.cop_order, defaults.cop_order);
+}
+
+/// Keeps a single cop of `kind`, far behind the thief, and lets the thief pick up a USB.
+fn cop_behind_usb_carrier(kind: CopKind) -> CopState {
+    let mut app = headless_app(Arena::default(), 5);
+    app.update();
+
+    single_cop_at(&mut app, Vec3::new(500., 0., 0.));
+    let player = app
+        .world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(&app.world)
+        .translation;
+    let mut enemy = app.world.query::<&mut Enemy>().single_mut(&mut app.world);
+    enemy.facing = Vec2::X;
+    enemy.kind = kind;
+
+    let mut q = app
+        .world
+        .query_filtered::<&mut Transform, (With<Usb>, Without<Player>)>();
+    q.iter_mut(&mut app.world).next().unwrap().translation = player;
+    app.update();
+    app.update();
+
+    app.world.query::<&Enemy>().single(&app.world).state.clone()
+}
+
+#[test]
+fn detectives_track_the_usb_carrier() {
+    assert!(matches!(
+        cop_behind_usb_carrier(CopKind::Officer),
+        CopState::Patrol
+    ));
+    assert!(matches!(
+        cop_behind_usb_carrier(CopKind::Detective),
+        CopState::Notice(_)
+    ));
+}
+
+#[test]
+fn clearance_fits_the_biggest_cop() {
+    let mut config = GameConfig::default();
+    config.cop_types.swat.hitbox = Shape::Circle(90.);
+    assert_eq!(config.clearance(), Vec2::splat(180.));
+}
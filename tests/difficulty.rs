@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use crime_download::{
+    arena::Arena,
+    config::GameConfig,
+    difficulty::{Difficulty, DifficultyCurve},
+    enemies::{CopKind, Enemy},
+    headless::{headless_app, TICK},
+    popups::PopupCommand,
+    Common,
+};
+use std::time::Duration;
+
+fn cops_at_start(difficulty: Difficulty) -> usize {
+    let mut app = headless_app(Arena::default(), 1);
+    app.insert_resource(difficulty);
+    app.update();
+    app.world
+        .query_filtered::<(), With<Enemy>>()
+        .iter(&app.world)
+        .count()
+}
+
+#[test]
+fn harder_presets_start_with_more_cops() {
+    let counts: Vec<usize> = Difficulty::ALL.into_iter().map(cops_at_start).collect();
+    assert_eq!(counts[1], GameConfig::default().starting_enemies as usize);
+    assert!(counts.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(counts[0] < counts[3]);
+}
+
+#[test]
+fn the_curve_grows_with_score_and_time_up_to_its_max() {
+    let curve = DifficultyCurve::default();
+    assert_eq!(curve.level(0, Duration::ZERO), 0.);
+    assert!(curve.level(0, Duration::ZERO) < curve.level(2, Duration::ZERO));
+    assert!(curve.level(2, Duration::ZERO) < curve.level(2, Duration::from_secs(60)));
+    assert_eq!(
+        curve.level(1000, Duration::from_secs(6000)),
+        curve.max_level
+    );
+}
+
+#[test]
+fn scoring_makes_the_cops_faster() {
+    let mut app = headless_app(Arena::default(), 1);
+    app.update();
+    let before = app.world.resource::<Common>().difficulty.cop_speed;
+    app.world.resource_mut::<Common>().score = 4;
+    app.update();
+    assert!(before < app.world.resource::<Common>().difficulty.cop_speed);
+}
+
+#[test]
+fn popups_send_in_the_next_cop_in_order() {
+    let mut app = headless_app(Arena::default(), 1);
+    app.update();
+    app.world.resource_mut::<GameConfig>().cop_order = vec![CopKind::Detective];
+    app.world.send_event(PopupCommand::AddCop);
+    let warning = GameConfig::default().spawn_warning_time;
+    for _ in 0..(warning / TICK.as_secs_f32()) as usize + 10 {
+        app.update();
+    }
+
+    let detectives = app
+        .world
+        .query::<&Enemy>()
+        .iter(&app.world)
+        .filter(|enemy| enemy.kind == CopKind::Detective)
+        .count();
+    assert_eq!(detectives, 1);
+}
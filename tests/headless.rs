@@ -0,0 +1,542 @@
+use bevy::{
+    hierarchy::despawn_with_children_recursive,
+    input::gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadInfo},
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use crime_download::{
+    arena::Arena,
+    config::GameConfig,
+    enemies::{CopState, Enemy},
+    headless::headless_app,
+    input::{Action, Binding, Bindings},
+    items::{Pc, Usb},
+    level::{CurrentLevel, Level},
+    obstacles::{Obstacle, ObstacleKind},
+    player::{AfterImage, Player},
+    popups::PopupCommand,
+    Common, State,
+};
+use std::time::Duration;
+
+mod common;
+use common::single_cop_at;
+
+fn app(seed: u64) -> App {
+    let mut app = headless_app(Arena::default(), seed);
+    app.update();
+    app
+}
+
+fn state(app: &App) -> State {
+    *app.world
+        .resource::<bevy::ecs::schedule::State<State>>()
+        .get()
+}
+
+fn player_position(app: &mut App) -> Vec3 {
+    app.world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(&app.world)
+        .translation
+}
+
+fn move_to<F: bevy::ecs::query::ReadOnlyWorldQuery>(app: &mut App, translation: Vec3) {
+    let mut q = app.world.query_filtered::<&mut Transform, F>();
+    q.iter_mut(&mut app.world).next().unwrap().translation = translation;
+}
+
+#[test]
+fn caught_by_cop_ends_the_run() {
+    let mut app = app(1);
+    assert_eq!(state(&app), State::InGame);
+    app.world.resource_mut::<GameConfig>().one_hit = true;
+
+    let player = player_position(&mut app);
+    move_to::<With<Enemy>>(&mut app, player);
+    app.update();
+    app.update();
+
+    assert_eq!(state(&app), State::GameOver);
+}
+
+#[test]
+fn caught_by_cop_costs_a_life() {
+    let mut app = app(1);
+    assert_eq!(app.world.resource::<Common>().lives, 3);
+
+    let player = player_position(&mut app);
+    move_to::<With<Enemy>>(&mut app, player);
+    app.update();
+    app.update();
+
+    assert_eq!(state(&app), State::InGame);
+    assert_eq!(app.world.resource::<Common>().lives, 2);
+    assert_ne!(player_position(&mut app), player);
+
+    // can't be caught again right away
+    let player = player_position(&mut app);
+    move_to::<With<Enemy>>(&mut app, player);
+    app.update();
+    app.update();
+    assert_eq!(app.world.resource::<Common>().lives, 2);
+}
+
+#[test]
+fn caught_thief_does_not_come_back_at_a_usb_spawn() {
+    let mut app = headless_app(Arena::default(), 1);
+    let (pc_slot, usb_spawn) = (Vec2::new(-200., 300.), Vec2::new(500., -300.));
+    app.insert_resource(CurrentLevel {
+        level: Level {
+            pc_slots: vec![pc_slot],
+            usb_spawns: vec![usb_spawn],
+            ..default()
+        },
+        ..default()
+    });
+    app.update();
+    single_cop_at(&mut app, Vec3::X);
+    app.update();
+    app.update();
+
+    assert_eq!(app.world.resource::<Common>().lives, 2);
+    assert_eq!(player_position(&mut app).truncate(), pc_slot);
+}
+
+#[test]
+fn caught_once_at_a_high_frame_rate() {
+    let mut app = app(1);
+    app.world.resource_mut::<GameConfig>().capture_grace = 0.;
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        Duration::from_secs(1) / 144,
+    ));
+
+    let player = player_position(&mut app);
+    move_to::<With<Enemy>>(&mut app, player);
+    // most frames have no physics step, and must not see where the thief was caught
+    for _ in 0..6 {
+        app.update();
+    }
+
+    assert_eq!(app.world.resource::<Common>().lives, 2);
+}
+
+#[test]
+fn holding_dash_only_dashes_once() {
+    let distance = |frames_held: usize| {
+        let mut app = app(6);
+        despawn_enemies(&mut app);
+        let start = player_position(&mut app).y;
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Down);
+        for frame in 0..12 {
+            let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+            if frame < frames_held {
+                keys.press(KeyCode::Space);
+            } else {
+                keys.release(KeyCode::Space);
+            }
+            app.update();
+        }
+        start - player_position(&mut app).y
+    };
+
+    let (tapped, held, walked) = (distance(1), distance(12), distance(0));
+    assert!(walked + 50. < tapped, "{tapped} dashing, {walked} walking");
+    assert!((tapped - held).abs() < 1., "{tapped} tapped, {held} held");
+}
+
+#[test]
+fn dashing_through_a_cop_is_safe() {
+    let mut app = app(1);
+    app.world.resource_mut::<GameConfig>().one_hit = true;
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .press(KeyCode::Space);
+    app.update();
+
+    let player = player_position(&mut app);
+    move_to::<With<Enemy>>(&mut app, player);
+    app.update();
+    app.update();
+
+    assert_eq!(state(&app), State::InGame);
+}
+
+#[test]
+fn after_images_do_not_depend_on_frame_rate() {
+    let after_images = |fps: u32| {
+        let mut app = app(6);
+        despawn_enemies(&mut app);
+        app.world.resource_mut::<GameConfig>().after_image_time = 10.;
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            Duration::from_secs(1) / fps,
+        ));
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Space);
+        for _ in 0..fps / 2 {
+            app.update();
+        }
+        app.world
+            .query_filtered::<(), With<AfterImage>>()
+            .iter(&app.world)
+            .count()
+    };
+
+    let (slow, fast) = (after_images(30), after_images(144));
+    assert!(0 < slow);
+    assert!(
+        slow.abs_diff(fast) <= 1,
+        "{slow} at 30 fps, {fast} at 144 fps"
+    );
+}
+
+#[test]
+fn delivering_usb_starts_download() {
+    let mut app = app(2);
+    for mut enemy in app
+        .world
+        .query_filtered::<&mut Transform, With<Enemy>>()
+        .iter_mut(&mut app.world)
+    {
+        enemy.translation = Vec3::new(10_000., 10_000., 0.);
+    }
+
+    let player = player_position(&mut app);
+    move_to::<With<Usb>>(&mut app, player);
+    app.update();
+
+    let pc = app
+        .world
+        .query_filtered::<&Transform, With<Pc>>()
+        .iter(&app.world)
+        .next()
+        .unwrap()
+        .translation;
+    move_to::<With<Player>>(&mut app, pc);
+    app.update();
+    app.update();
+
+    assert!(app
+        .world
+        .query::<&Pc>()
+        .iter(&app.world)
+        .any(|pc| pc.has_usb));
+}
+
+#[test]
+fn same_seed_plays_out_the_same() {
+    let positions = |seed| {
+        let mut app = app(seed);
+        for _ in 0..300 {
+            app.update();
+        }
+        app.world
+            .query_filtered::<&Transform, With<Enemy>>()
+            .iter(&app.world)
+            .map(|trans| trans.translation)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(positions(3), positions(3));
+    assert_ne!(positions(3), positions(4));
+}
+
+#[test]
+fn movement_does_not_depend_on_frame_rate() {
+    let distance = |fps: u32| {
+        let mut app = app(5);
+        despawn_enemies(&mut app);
+
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            Duration::from_secs(1) / fps,
+        ));
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Right);
+        for _ in 0..fps {
+            app.update();
+        }
+        player_position(&mut app).x
+    };
+
+    let (slow, fast) = (distance(30), distance(144));
+    assert!(100. < slow);
+    assert!(
+        (slow - fast).abs() < 10.,
+        "{slow} at 30 fps, {fast} at 144 fps"
+    );
+}
+
+fn despawn_enemies(app: &mut App) {
+    let enemies = app
+        .world
+        .query_filtered::<Entity, With<Enemy>>()
+        .iter(&app.world)
+        .collect::<Vec<_>>();
+    for enemy in enemies {
+        despawn_with_children_recursive(&mut app.world, enemy);
+    }
+}
+
+#[test]
+fn stick_tilt_sets_the_speed() {
+    let distance = |tilt: f32| {
+        let mut app = app(7);
+        despawn_enemies(&mut app);
+
+        let gamepad = Gamepad::new(0);
+        app.world.send_event(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected(GamepadInfo {
+                name: "test pad".to_string(),
+            }),
+        ));
+        app.update();
+        let start = player_position(&mut app);
+
+        app.world
+            .resource_mut::<Axis<GamepadAxis>>()
+            .set(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY), tilt);
+        for _ in 0..30 {
+            app.update();
+        }
+        player_position(&mut app).y - start.y
+    };
+
+    let (half, full) = (distance(0.5), distance(1.));
+    assert!(50. < full);
+    assert!(
+        (half * 2. - full).abs() < 5.,
+        "{half} at half tilt, {full} at full tilt"
+    );
+}
+
+fn cop_state(app: &mut App) -> CopState {
+    app.world
+        .query::<&Enemy>()
+        .iter(&app.world)
+        .next()
+        .unwrap()
+        .state
+        .clone()
+}
+
+#[test]
+fn cop_notices_and_pursues_the_thief() {
+    let mut app = app(9);
+    single_cop_at(&mut app, Vec3::new(200., 0., 0.));
+
+    app.update();
+    assert!(matches!(cop_state(&mut app), CopState::Notice(_)));
+    for _ in 0..30 {
+        app.update();
+    }
+    assert!(matches!(cop_state(&mut app), CopState::Pursue));
+}
+
+#[test]
+fn cop_searches_then_gives_up() {
+    let mut app = app(10);
+    single_cop_at(&mut app, Vec3::new(200., 0., 0.));
+    app.world
+        .query::<&mut Enemy>()
+        .single_mut(&mut app.world)
+        .state = CopState::Pursue;
+    move_to::<With<Player>>(&mut app, Vec3::new(-600., 0., 0.));
+
+    app.update();
+    assert!(matches!(cop_state(&mut app), CopState::Search(_)));
+    for _ in 0..60 * 5 {
+        app.update();
+    }
+    assert!(matches!(cop_state(&mut app), CopState::Patrol));
+}
+
+fn spawn_wall(app: &mut App, center: Vec3) {
+    app.world.spawn((
+        Obstacle {
+            kind: ObstacleKind::Wall,
+            size: Vec2::new(20., 200.),
+        },
+        Transform::from_translation(center),
+    ));
+}
+
+#[test]
+fn walls_block_the_thief() {
+    let mut app = app(11);
+    despawn_enemies(&mut app);
+    let start = player_position(&mut app);
+    spawn_wall(&mut app, start + Vec3::new(150., 0., 0.));
+
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .press(KeyCode::Right);
+    for _ in 0..60 {
+        app.update();
+    }
+    let x = player_position(&mut app).x - start.x;
+    assert!(100. < x && x <= 115., "{x}");
+}
+
+#[test]
+fn walls_block_the_cops_sight() {
+    let mut app = app(12);
+    single_cop_at(&mut app, Vec3::new(200., 0., 0.));
+    let player = player_position(&mut app);
+    spawn_wall(&mut app, player + Vec3::new(100., 0., 0.));
+
+    for _ in 0..30 {
+        app.update();
+    }
+    assert!(matches!(cop_state(&mut app), CopState::Patrol));
+}
+
+#[test]
+fn cops_walk_around_walls() {
+    let mut app = app(13);
+    let obstacles = app
+        .world
+        .query_filtered::<Entity, With<Obstacle>>()
+        .iter(&app.world)
+        .collect::<Vec<_>>();
+    for obstacle in obstacles {
+        app.world.despawn(obstacle);
+    }
+    move_to::<With<Player>>(&mut app, Vec3::new(500., 300., 0.));
+    single_cop_at(&mut app, Vec3::new(-600., -300., 0.));
+    spawn_wall(&mut app, Vec3::ZERO);
+
+    let goal = Vec2::new(100., 0.);
+    {
+        let mut q = app.world.query::<(&mut Transform, &mut Enemy)>();
+        let (mut trans, mut enemy) = q.single_mut(&mut app.world);
+        trans.translation = Vec3::new(-100., 0., 0.);
+        enemy.goal = goal;
+        enemy.change_goal = Timer::from_seconds(100., TimerMode::Repeating);
+    }
+
+    for _ in 0..60 * 10 {
+        app.update();
+    }
+    let cop = app
+        .world
+        .query_filtered::<&Transform, With<Enemy>>()
+        .single(&app.world)
+        .translation;
+    assert!(cop.truncate().distance(goal) < 20., "{cop}");
+}
+
+#[test]
+fn bindings_trigger_one_action_each() {
+    let mut bindings = Bindings::default();
+    let all: Vec<Binding> = Action::ALL
+        .iter()
+        .flat_map(|action| bindings.get(*action).to_vec())
+        .collect();
+    for binding in all.iter() {
+        assert_eq!(
+            all.iter().filter(|other| *other == binding).count(),
+            1,
+            "{binding:?} is bound twice"
+        );
+    }
+
+    bindings.rebind(Action::Pause, Binding::Key(KeyCode::Space));
+    assert_eq!(
+        bindings.action(Binding::Key(KeyCode::Space)),
+        Some(Action::Pause)
+    );
+    assert!(!bindings
+        .get(Action::Dash)
+        .contains(&Binding::Key(KeyCode::Space)));
+}
+
+#[test]
+fn rebound_keys_move_the_player() {
+    let mut app = app(8);
+    despawn_enemies(&mut app);
+    app.world
+        .resource_mut::<Bindings>()
+        .rebind(Action::MoveRight, Binding::Key(KeyCode::L));
+    let start = player_position(&mut app);
+
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::D);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(start, player_position(&mut app));
+
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::L);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(start.x < player_position(&mut app).x);
+}
+
+#[test]
+fn pausing_freezes_the_run() {
+    let mut app = app(6);
+    let enemies = |app: &mut App| {
+        app.world
+            .query_filtered::<&Transform, With<Enemy>>()
+            .iter(&app.world)
+            .map(|trans| trans.translation)
+            .collect::<Vec<_>>()
+    };
+    for _ in 0..10 {
+        app.update();
+    }
+
+    app.world
+        .resource_mut::<NextState<State>>()
+        .set(State::Paused);
+    app.update();
+    let before = enemies(&mut app);
+    for _ in 0..60 {
+        app.update();
+    }
+    assert_eq!(state(&app), State::Paused);
+    assert_eq!(before, enemies(&mut app));
+
+    app.world
+        .resource_mut::<NextState<State>>()
+        .set(State::InGame);
+    app.update();
+    app.update();
+    assert_eq!(state(&app), State::InGame);
+    assert_eq!(before.len(), enemies(&mut app).len());
+    assert_ne!(before, enemies(&mut app));
+    assert_eq!(
+        app.world
+            .query_filtered::<(), With<Player>>()
+            .iter(&app.world)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn popups_closed_while_paused_count_after_resuming() {
+    let mut app = app(6);
+    let speed = app.world.resource::<Common>().enemy_speed;
+    app.world
+        .resource_mut::<NextState<State>>()
+        .set(State::Paused);
+    app.update();
+
+    app.world.send_event(PopupCommand::IncreaseCopSpeed);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(app.world.resource::<Common>().enemy_speed, speed);
+
+    app.world
+        .resource_mut::<NextState<State>>()
+        .set(State::InGame);
+    app.update();
+    assert!(speed < app.world.resource::<Common>().enemy_speed);
+}
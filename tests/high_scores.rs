@@ -0,0 +1,34 @@
+use crime_download::high_scores::*;
+use std::time::Duration;
+
+fn entry(score: u32) -> HighScore {
+    HighScore {
+        score,
+        date: 0,
+        seed: 0,
+        length: Duration::ZERO,
+    }
+}
+
+#[test]
+fn table_keeps_the_best_scores_in_order() {
+    let mut table = HighScores::default();
+    for score in 0..MAX_HIGH_SCORES as u32 {
+        assert_eq!(table.insert(entry(score)), Some(0));
+    }
+
+    assert_eq!(table.insert(entry(0)), None);
+    assert_eq!(table.insert(entry(5)), Some(5));
+    assert_eq!(table.entries.len(), MAX_HIGH_SCORES);
+    assert!(table
+        .entries
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+}
+
+#[test]
+fn dates_are_formatted_in_utc() {
+    assert_eq!(format_date(0), "1970-01-01");
+    assert_eq!(format_date(951_782_400), "2000-02-29");
+    assert_eq!(format_date(1_792_281_600), "2026-10-18");
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use crime_download::{
+    arena::Arena,
+    config::GameConfig,
+    generator::{generate_level, MIN_COP_DISTANCE, MIN_LEVEL_SIZE},
+    headless::headless_app,
+    items::Pc,
+    level::{CurrentLevel, Level, LEVEL_PATHS},
+    pathfinding::NavGrid,
+    player::Player,
+};
+
+mod common;
+use common::load_level;
+
+#[test]
+fn level_spots_are_inside_the_arena_and_free() {
+    let size = GameConfig::default().clearance();
+    for path in LEVEL_PATHS {
+        let level = load_level(path);
+        let arena = Rect::from_center_size(Vec2::ZERO, level.size);
+        let spots = std::iter::once(level.player_start)
+            .chain(level.pc_slots.iter().copied())
+            .chain(level.usb_spawns.iter().copied())
+            .chain(level.cop_doors.iter().copied());
+
+        for spot in spots {
+            assert!(arena.contains(spot), "{spot} is outside of {path}");
+            let rect = Rect::from_center_size(spot, size);
+            for obstacle in level.obstacles.iter() {
+                let obstacle = Rect::from_center_size(obstacle.center, obstacle.size);
+                assert!(
+                    obstacle.intersect(rect).is_empty(),
+                    "{spot} is inside an obstacle in {path}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn runs_use_the_level_layout() {
+    let path = LEVEL_PATHS[1];
+    let level = load_level(path);
+    let mut app = headless_app(Arena::default(), 1);
+    app.insert_resource(CurrentLevel {
+        path: Some(path.to_string()),
+        level: level.clone(),
+        ..default()
+    });
+    app.update();
+
+    let player = app
+        .world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(&app.world)
+        .translation
+        .truncate();
+    assert_eq!(player, level.player_start);
+
+    for pc in app
+        .world
+        .query_filtered::<&Transform, With<Pc>>()
+        .iter(&app.world)
+    {
+        assert!(level.pc_slots.contains(&pc.translation.truncate()));
+    }
+}
+
+/// Checks that there are at least `min_spots` PC slots and USB spawns, and that all of them can
+/// be reached and are away from the cops.
+fn assert_fair_spots(level: &Level, min_spots: usize, seed: u64) {
+    let clearance = GameConfig::default().clearance();
+    assert!(
+        min_spots <= level.pc_slots.len(),
+        "too few PC slots with seed {seed}"
+    );
+    assert!(
+        min_spots <= level.usb_spawns.len(),
+        "too few USB spawns with seed {seed}"
+    );
+
+    let obstacles: Vec<Rect> = level
+        .obstacles
+        .iter()
+        .map(|obstacle| Rect::from_center_size(obstacle.center, obstacle.size))
+        .collect();
+    let grid = NavGrid::new(&Arena::new(level.size), &obstacles, clearance);
+    for door in level.cop_doors.iter() {
+        assert!(grid.find_path(level.player_start, *door).is_some());
+    }
+    for spot in level.pc_slots.iter().chain(level.usb_spawns.iter()) {
+        assert!(
+            grid.find_path(level.player_start, *spot).is_some(),
+            "{spot} can't be reached with seed {seed}"
+        );
+        for door in level.cop_doors.iter() {
+            assert!(
+                MIN_COP_DISTANCE <= spot.distance(*door),
+                "{spot} is next to the cops with seed {seed}"
+            );
+        }
+    }
+}
+
+#[test]
+fn generated_spots_are_reachable_and_away_from_the_cops() {
+    let clearance = GameConfig::default().clearance();
+    for seed in 0..20 {
+        let level = generate_level(seed, Arena::default().size, clearance);
+        // at this size there is room away from the cops in at least two rooms on each side
+        assert_fair_spots(&level, 4, seed);
+    }
+}
+
+#[test]
+fn small_levels_are_grown() {
+    let clearance = GameConfig::default().clearance();
+    for seed in 0..50 {
+        let level = generate_level(seed, Vec2::new(400., 300.), clearance);
+        assert_eq!(level.size, MIN_LEVEL_SIZE);
+        assert_fair_spots(&level, 1, seed);
+    }
+}
+
+#[test]
+fn generated_levels_follow_the_seed() {
+    let mut app = headless_app(Arena::default(), 3);
+    app.insert_resource(CurrentLevel {
+        generated: true,
+        ..default()
+    });
+    app.update();
+
+    let level = app.world.resource::<CurrentLevel>().level.clone();
+    let expected = generate_level(3, level.size, GameConfig::default().clearance());
+    assert_eq!(format!("{level:?}"), format!("{expected:?}"));
+    assert_eq!(
+        app.world
+            .query_filtered::<(), With<crime_download::obstacles::Obstacle>>()
+            .iter(&app.world)
+            .count(),
+        level.obstacles.len()
+    );
+}
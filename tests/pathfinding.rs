@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use crime_download::{
+    arena::Arena,
+    config::GameConfig,
+    pathfinding::{NavGrid, Path, REPLAN_DISTANCE},
+};
+
+#[test]
+fn paths_are_searched_again_when_the_goal_moves_far_or_behind_a_wall() {
+    let wall = Rect::from_center_size(Vec2::ZERO, Vec2::new(40., 200.));
+    let grid = NavGrid::new(
+        &Arena::default(),
+        &[wall],
+        GameConfig::default().clearance(),
+    );
+    let start = Vec2::new(-300., 0.);
+    let (behind, beside, away) = (
+        Vec2::new(60., 160.),
+        Vec2::new(60., 200.),
+        Vec2::new(60., 260.),
+    );
+    assert!(!grid.clear_line(start, behind));
+    assert!(grid.clear_line(start, beside));
+    assert!(behind.distance(beside) < REPLAN_DISTANCE);
+    assert!(REPLAN_DISTANCE < behind.distance(away));
+
+    let mut path = Path::default();
+    path.next_waypoint(start, behind, &grid, 10.);
+    let corners = path.waypoints().count();
+    assert!(1 < corners);
+
+    path.next_waypoint(start, beside, &grid, 10.);
+    assert_eq!(path.waypoints().count(), corners);
+    assert_eq!(path.waypoints().last(), Some(beside));
+
+    // a small move that would take the last leg through the wall
+    let blocked = Vec2::new(60., 110.);
+    assert!(behind.distance(blocked) < REPLAN_DISTANCE);
+    path.next_waypoint(start, blocked, &grid, 10.);
+    let points: Vec<Vec2> = std::iter::once(start).chain(path.waypoints()).collect();
+    assert_eq!(points.last(), Some(&blocked));
+    assert!(points.windows(2).all(|leg| grid.clear_line(leg[0], leg[1])));
+
+    path.next_waypoint(start, away, &grid, 10.);
+    assert_eq!(path.waypoints().collect::<Vec<_>>(), vec![away]);
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use crime_download::{
+    arena::Arena,
+    config::{ConfigPlugin, GameConfig, GameConfigHandle},
+    headless::headless_app,
+    replay::{Recorder, Run},
+    State,
+};
+use std::time::Duration;
+
+#[test]
+fn recordings_keep_the_config() {
+    let config = GameConfig {
+        player_speed: 123.,
+        one_hit: true,
+        ..GameConfig::default()
+    };
+    let run = Run {
+        config: Some(config.clone()),
+        ..Run::default()
+    };
+
+    let loaded: Run = ron::from_str(&ron::to_string(&run).unwrap()).unwrap();
+    assert_eq!(
+        format!("{:?}", loaded.config),
+        format!("{:?}", Some(config))
+    );
+}
+
+#[test]
+fn config_changes_wait_for_the_recorded_run_to_end() {
+    let mut app = headless_app(Arena::default(), 1);
+    app.add_plugins(ConfigPlugin).insert_resource(Recorder::new(
+        std::env::temp_dir().join("crime_download_reload_test.ron"),
+    ));
+    app.update();
+
+    let handle = app.world.resource::<GameConfigHandle>().0.clone();
+    for _ in 0..500 {
+        if app.world.resource::<Assets<GameConfig>>().contains(&handle) {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+        app.update();
+    }
+    app.world
+        .resource_mut::<Assets<GameConfig>>()
+        .set_untracked(
+            handle.clone(),
+            GameConfig {
+                player_speed: 123.,
+                ..GameConfig::default()
+            },
+        );
+    app.world
+        .resource_mut::<Events<AssetEvent<GameConfig>>>()
+        .send(AssetEvent::Modified { handle });
+    app.update();
+    app.update();
+    assert_ne!(app.world.resource::<GameConfig>().player_speed, 123.);
+
+    app.insert_resource(NextState(Some(State::GameOver)));
+    app.update();
+    app.update();
+    assert_eq!(app.world.resource::<GameConfig>().player_speed, 123.);
+}
@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use crime_download::{
+    arena::Arena,
+    config::GameConfig,
+    enemies::{AddEnemy, CopKind, Enemy},
+    headless::{headless_app, TICK},
+    pathfinding::NavGrid,
+    player::Player,
+    spawning::{place_item, Occupied, SpawnRules, SpawnWarning},
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+#[test]
+fn items_keep_away_from_the_thief_and_the_cops() {
+    let rules = SpawnRules::new(&GameConfig::default());
+    let spots = [
+        Vec2::new(10., 0.),
+        Vec2::new(300., 50.),
+        Vec2::new(-400., 0.),
+        Vec2::new(0., 300.),
+    ];
+    let occupied = Occupied {
+        player: Some(Vec2::ZERO),
+        cops: &[Vec2::new(-350., 0.), Vec2::new(0., 250.)],
+        items: &[],
+    };
+    for seed in 0..10 {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let position = place_item(
+            &spots,
+            Vec2::ZERO,
+            &Arena::default(),
+            &NavGrid::new(&Arena::default(), &[], rules.clearance).with_reachable_from(Vec2::ZERO),
+            &rules,
+            &occupied,
+            &mut rng,
+        );
+        assert_eq!(position, spots[1]);
+    }
+}
+
+#[test]
+fn new_cops_are_telegraphed_away_from_the_thief() {
+    let mut app = headless_app(Arena::default(), 4);
+    app.update();
+    let cops = |app: &mut App| {
+        app.world
+            .query_filtered::<(), With<Enemy>>()
+            .iter(&app.world)
+            .count()
+    };
+    let warnings = |app: &mut App| app.world.query::<&SpawnWarning>().iter(&app.world).count();
+    let before = cops(&mut app);
+
+    app.world.send_event(AddEnemy(CopKind::Officer));
+    app.update();
+    assert_eq!(cops(&mut app), before);
+    assert_eq!(warnings(&mut app), 1);
+
+    let warning_time = app.world.resource::<GameConfig>().spawn_warning_time;
+    for _ in 0..(warning_time / TICK.as_secs_f32()) as usize + 2 {
+        app.update();
+    }
+    assert_eq!(cops(&mut app), before + 1);
+    assert_eq!(warnings(&mut app), 0);
+
+    let player = app
+        .world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(&app.world)
+        .translation;
+    let min_distance = app.world.resource::<GameConfig>().spawn_player_distance;
+    assert!(app
+        .world
+        .query_filtered::<&Transform, With<Enemy>>()
+        .iter(&app.world)
+        .any(|cop| min_distance <= cop.translation.distance(player)));
+}
+
+#[test]
+fn items_without_spots_are_placed_where_the_thief_can_walk() {
+    let rules = SpawnRules::new(&GameConfig::default());
+    let arena = Arena::default();
+    // a wall across the whole arena, with the thief on the left of it
+    let wall = Rect::from_center_size(Vec2::ZERO, Vec2::new(20., arena.size.y));
+    let occupied = Occupied {
+        player: Some(Vec2::new(-300., 0.)),
+        cops: &[],
+        items: &[],
+    };
+    let grid =
+        NavGrid::new(&arena, &[wall], rules.clearance).with_reachable_from(Vec2::new(-300., 0.));
+    for seed in 0..10 {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let position = place_item(
+            &[],
+            Vec2::new(-300., 0.),
+            &arena,
+            &grid,
+            &rules,
+            &occupied,
+            &mut rng,
+        );
+        assert!(position.x < 0., "{position} is behind the wall");
+    }
+}
+
+#[test]
+fn warnings_share_their_assets() {
+    let mut app = headless_app(Arena::default(), 4);
+    app.update();
+    let assets = |app: &App| {
+        (
+            app.world.resource::<Assets<Mesh>>().len(),
+            app.world.resource::<Assets<ColorMaterial>>().len(),
+        )
+    };
+    let before = assets(&app);
+
+    for _ in 0..3 {
+        app.world.send_event(AddEnemy(CopKind::Officer));
+    }
+    app.update();
+    assert_eq!(
+        app.world.query::<&SpawnWarning>().iter(&app.world).count(),
+        3
+    );
+    assert_eq!(assets(&app), before);
+}